@@ -1,11 +1,12 @@
 import { useState, useEffect } from "react";
-import { Box, Typography, Button, Chip, Select, MenuItem, FormControl, InputLabel } from "@mui/material";
+import { Box, Typography, Button, Chip, Select, MenuItem, FormControl, InputLabel, ListSubheader } from "@mui/material";
 import PlayArrowIcon from "@mui/icons-material/PlayArrow";
 import StopIcon from "@mui/icons-material/Stop";
 import SportsEsportsIcon from "@mui/icons-material/SportsEsports";
 import { convertFileSrc } from "@tauri-apps/api/core";
 import { Game, useGames } from "../context/GameContext";
 import { gameService, wineService } from "../services";
+import { RUNNER_FAMILY_LABELS } from "../services/WineService";
 import GameLog from "./GameLog";
 import ConfirmationModal from "./ConfirmationModal";
 
@@ -240,11 +241,15 @@ export default function GameDetail({ game }: GameDetailProps) {
                     {selectedWineVersion.split('/').pop() || selectedWineVersion} (current)
                   </MenuItem>
                 )}
-                {availableWineVersions.map((version) => (
+                {availableWineVersions.flatMap((version, index) => [
+                  // Header before the first version of each family
+                  ...(index === 0 || availableWineVersions[index - 1].family !== version.family
+                    ? [<ListSubheader key={`family-${version.family}`}>{RUNNER_FAMILY_LABELS[version.family]}</ListSubheader>]
+                    : []),
                   <MenuItem key={version.path} value={version.path}>
                     {version.display_name}
-                  </MenuItem>
-                ))}
+                  </MenuItem>,
+                ])}
               </Select>
             </FormControl>
           </Box>
@@ -45,10 +45,17 @@ export default function ProtonDownloader({
     setLoading(true);
     setError(null);
     try {
-      const fetchedReleases = await wineService.fetchGeProtonReleases();
+      const { releases: fetchedReleases, rate_limited } = await wineService.fetchGeProtonReleases();
       console.log("📥 Fetched GE-Proton releases:", fetchedReleases);
       setReleases(fetchedReleases);
 
+      if (rate_limited) {
+        const resetAt = new Date(rate_limited.reset_at).toLocaleTimeString();
+        const warning = `GitHub rate limit reached, showing cached releases until ${resetAt}`;
+        setError(warning);
+        if (onError) onError(warning);
+      }
+
       // Don't auto-select anything - let user choose
       // (Previous auto-select logic removed for better UX)
     } catch (err) {
@@ -76,7 +83,8 @@ export default function ProtonDownloader({
       // Download and install the selected GE-Proton version
       const installedPath = await wineService.downloadGeProton(
         release.tag_name,
-        release.download_url
+        release.download_url,
+        release.checksum_url
       );
 
       console.log("✅ Installed to:", installedPath);
@@ -7,6 +7,7 @@ import {
   Typography,
   Select,
   MenuItem,
+  ListSubheader,
   FormControl,
   InputLabel,
   IconButton,
@@ -17,11 +18,13 @@ import {
 import CloseIcon from "@mui/icons-material/Close";
 import ArrowBackIcon from "@mui/icons-material/ArrowBack";
 import { wineService } from "../services";
+import { RUNNER_FAMILY_LABELS, type RunnerFamily } from "../services/WineService";
 import WineRunnerManager from "./WineRunnerManager";
 
 interface WineVersionInfo {
   path: string;
   display_name: string;
+  family: RunnerFamily;
 }
 
 interface SettingsModalProps {
@@ -241,11 +244,15 @@ export default function SettingsModal({ open, onClose }: SettingsModalProps) {
                         {defaultWineVersion.split('/').pop() || defaultWineVersion} (current)
                       </MenuItem>
                     )}
-                    {wineVersions.map((version) => (
+                    {wineVersions.flatMap((version, index) => [
+                      // Header before the first version of each family
+                      ...(index === 0 || wineVersions[index - 1].family !== version.family
+                        ? [<ListSubheader key={`family-${version.family}`}>{RUNNER_FAMILY_LABELS[version.family]}</ListSubheader>]
+                        : []),
                       <MenuItem key={version.path} value={version.path}>
                         {version.display_name}
-                      </MenuItem>
-                    ))}
+                      </MenuItem>,
+                    ])}
                   </Select>
                 </FormControl>
 
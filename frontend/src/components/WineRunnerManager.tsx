@@ -74,12 +74,17 @@ export default function WineRunnerManager({ onRunnersChanged }: WineRunnerManage
     setErrorMessage("");
     try {
       // Load GE-Proton releases from GitHub
-      const fetchedReleases = await wineService.fetchGeProtonReleases();
+      const { releases: fetchedReleases, rate_limited } = await wineService.fetchGeProtonReleases();
       console.log("📥 Fetched GE-Proton releases:", fetchedReleases);
       setReleases(fetchedReleases);
 
+      if (rate_limited) {
+        const resetAt = new Date(rate_limited.reset_at).toLocaleTimeString();
+        showError(`GitHub rate limit reached, showing cached releases until ${resetAt}`);
+      }
+
       // Load all installed versions
-      const allVersions = await wineService.getAvailableVersions();
+      const allVersions = await wineService.getVersionsWithDetails();
       setAllInstalledVersions(allVersions);
     } catch (error) {
       console.error("Failed to load data:", error);
@@ -99,7 +104,8 @@ export default function WineRunnerManager({ onRunnersChanged }: WineRunnerManage
 
       const installedPath = await wineService.downloadGeProton(
         release.tag_name,
-        release.download_url
+        release.download_url,
+        release.checksum_url
       );
 
       console.log("✅ Installed to:", installedPath);
@@ -137,7 +143,7 @@ export default function WineRunnerManager({ onRunnersChanged }: WineRunnerManage
 
     try {
       await wineService.deleteProtonVersion(path);
-      showSuccess(`Deleted ${releaseName}`);
+      showSuccess(`Moved ${releaseName} to the trash`);
       await loadData();
       await refreshWineVersions();
       if (onRunnersChanged) onRunnersChanged();
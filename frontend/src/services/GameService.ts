@@ -24,6 +24,22 @@ export interface GameRunningStatus {
   pids: string[];
 }
 
+/**
+ * One environment variable, in config order
+ */
+export interface EnvVar {
+  name: string;
+  value: string;
+}
+
+/**
+ * Config the environment variables live in: a game, runners/wine.yml or system.yml
+ */
+export type EnvScope =
+  | { type: "game"; slug: string }
+  | { type: "runner" }
+  | { type: "system" };
+
 export interface Game {
   slug: string;
   name: string;
@@ -34,11 +50,112 @@ export interface Game {
   executable: string | null;
   wine_version: string | null;
   wine_prefix: string | null;
-  environment_vars: string | null;
+  environment_vars: EnvVar[];
   cover_url: string | null;
   debug_output: boolean;
 }
 
+/**
+ * Wine/Proton runner options, null means not set at that level
+ */
+export interface GameWineOptions {
+  dxvk: boolean | null;
+  dxvk_version: string | null;
+  vkd3d: boolean | null;
+  vkd3d_version: string | null;
+  d3d_extras: boolean | null;
+  d3d_extras_version: string | null;
+  dxvk_nvapi: boolean | null;
+  dxvk_nvapi_version: string | null;
+  esync: boolean | null;
+  fsync: boolean | null;
+  fsr: boolean | null;
+  battleye: boolean | null;
+  eac: boolean | null;
+  virtual_desktop: boolean | null;
+  /** e.g. "1920x1080" */
+  desktop_resolution: string | null;
+  /** auto, alsa, pulse or oss */
+  audio_driver: string | null;
+  show_debug: string | null;
+}
+
+/**
+ * A game's own options next to the defaults it inherits from runners/wine.yml
+ */
+export interface GameWineOptionsView {
+  game: GameWineOptions;
+  defaults: GameWineOptions;
+}
+
+/**
+ * How Wine loads a DLL
+ */
+export type DllMode =
+  | "native"
+  | "builtin"
+  | "native,builtin"
+  | "builtin,native"
+  | "disabled";
+
+export interface DllOverride {
+  /** Module name without ".dll", e.g. "d3d9" */
+  dll: string;
+  mode: DllMode;
+}
+
+export interface EffectiveDllOverride extends DllOverride {
+  source: "runner" | "game";
+}
+
+/**
+ * Overrides set at one level, what it inherits from runners/wine.yml and the merged result
+ */
+export interface DllOverridesView {
+  own: DllOverride[];
+  inherited: DllOverride[];
+  effective: EffectiveDllOverride[];
+}
+
+export interface DllPreset {
+  id: string;
+  name: string;
+  description: string;
+  overrides: DllOverride[];
+}
+
+/**
+ * Config the DLL overrides live in: a game or runners/wine.yml
+ */
+export type OverrideScope = { type: "game"; slug: string } | { type: "runner" };
+
+/**
+ * Config level a value is set at: system.yml, runners/<runner>.yml or the game config
+ */
+export type ConfigLevel = "system" | "runner" | "game";
+
+export interface ResolvedOption {
+  key: string;
+  value: unknown;
+  /** Level the value comes from */
+  level: ConfigLevel;
+  /** Lower levels that set the key too, their values are not used */
+  overridden: ConfigLevel[];
+  /** Per-entry provenance of mappings merged key by key, such as system.env */
+  entries?: ResolvedOption[];
+}
+
+/**
+ * A game's config after merging system.yml, runners/<runner>.yml and the game config
+ */
+export interface EffectiveConfig {
+  game_slug: string;
+  runner: string | null;
+  game: ResolvedOption[];
+  runner_options: ResolvedOption[];
+  system: ResolvedOption[];
+}
+
 class GameService {
   /**
    * Get all games
@@ -90,6 +207,97 @@ class GameService {
     return invoke("update_game_wine_version", { slug, wineVersion });
   }
 
+  /**
+   * Get a game's Wine/Proton options and the defaults it inherits
+   */
+  async getWineOptions(slug: string): Promise<GameWineOptionsView> {
+    return invoke<GameWineOptionsView>("get_game_wine_options", { slug });
+  }
+
+  /**
+   * Replace a game's Wine/Proton options, null options inherit the default
+   */
+  async setWineOptions(slug: string, options: GameWineOptions): Promise<void> {
+    return invoke("set_game_wine_options", { slug, options });
+  }
+
+  /**
+   * Get the environment variables of a game, the wine runner or the system config
+   */
+  async getEnvVars(scope: EnvScope): Promise<EnvVar[]> {
+    return invoke<EnvVar[]>("get_env_vars", { scope });
+  }
+
+  /**
+   * Add an environment variable after the existing ones
+   */
+  async addEnvVar(scope: EnvScope, variable: EnvVar): Promise<EnvVar[]> {
+    return invoke<EnvVar[]>("add_env_var", { scope, var: variable });
+  }
+
+  /**
+   * Change the value and/or name of an environment variable, it keeps its position
+   */
+  async updateEnvVar(scope: EnvScope, name: string, variable: EnvVar): Promise<EnvVar[]> {
+    return invoke<EnvVar[]>("update_env_var", { scope, name, var: variable });
+  }
+
+  /**
+   * Remove an environment variable
+   */
+  async removeEnvVar(scope: EnvScope, name: string): Promise<EnvVar[]> {
+    return invoke<EnvVar[]>("remove_env_var", { scope, name });
+  }
+
+  /**
+   * Put the environment variables in a new order, names must list every variable once
+   */
+  async reorderEnvVars(scope: EnvScope, names: string[]): Promise<EnvVar[]> {
+    return invoke<EnvVar[]>("reorder_env_vars", { scope, names });
+  }
+
+  /**
+   * Get the DLL overrides of a game or the wine runner, with the merged result
+   */
+  async getDllOverrides(scope: OverrideScope): Promise<DllOverridesView> {
+    return invoke<DllOverridesView>("get_dll_overrides", { scope });
+  }
+
+  /**
+   * Add or change one DLL override
+   */
+  async setDllOverride(scope: OverrideScope, dll: string, mode: DllMode): Promise<void> {
+    return invoke("set_dll_override", { scope, dll, mode });
+  }
+
+  /**
+   * Remove one DLL override
+   */
+  async removeDllOverride(scope: OverrideScope, dll: string): Promise<void> {
+    return invoke("remove_dll_override", { scope, dll });
+  }
+
+  /**
+   * List the built-in DLL override presets (mod loaders, ReShade)
+   */
+  async listDllPresets(): Promise<DllPreset[]> {
+    return invoke<DllPreset[]>("list_dll_presets");
+  }
+
+  /**
+   * Add the overrides of a preset
+   */
+  async applyDllPreset(scope: OverrideScope, presetId: string): Promise<void> {
+    return invoke("apply_dll_preset", { scope, presetId });
+  }
+
+  /**
+   * Get a game's effective config and the level every value comes from
+   */
+  async getEffectiveConfig(slug: string): Promise<EffectiveConfig> {
+    return invoke<EffectiveConfig>("get_effective_config", { slug });
+  }
+
   /**
    * Clear the log buffer for a game
    */
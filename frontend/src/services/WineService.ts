@@ -4,9 +4,99 @@
 
 import { invoke } from "@tauri-apps/api/core";
 
+export interface RunnerRecord {
+  path: string;
+  name: string;
+  source: RunnerSource | null;
+  release_tag: string | null;
+  imported_from: string | null;
+  installed_at: string;
+  checksum: string | null;
+  /** The archive matched the checksum published by its source */
+  verified: boolean;
+  archive_size: number;
+  disk_size: number;
+}
+
+export interface RunnerGame {
+  slug: string;
+  name: string;
+}
+
+export interface RunnerUsage {
+  path: string;
+  display_name: string;
+  games: RunnerGame[];
+  is_lutris_default: boolean;
+  is_managed: boolean;
+}
+
+export interface RunnerDeleteReport {
+  path: string;
+  replacement: string | null;
+  migrated_games: RunnerGame[];
+  trash_id: string;
+}
+
+export interface TrashEntry {
+  id: string;
+  name: string;
+  original_path: string;
+  trash_path: string;
+  deleted_at: string;
+  size_bytes: number;
+  record: RunnerRecord | null;
+}
+
+export interface DedupeReport {
+  dry_run: boolean;
+  runners_scanned: number;
+  files_scanned: number;
+  files_linked: number;
+  bytes_saved: number;
+}
+
+export interface UndedupeReport {
+  files_copied: number;
+  bytes_copied: number;
+}
+
+export interface PruneReport {
+  dry_run: boolean;
+  removed: { path: string; display_name: string; size_bytes: number }[];
+  kept: { path: string; display_name: string; reason: string }[];
+  freed_bytes: number;
+}
+
+export type RunnerFamily =
+  | "ge-proton"
+  | "wine-ge"
+  | "proton"
+  | "lutris-wine"
+  | "other"
+  | "system-wine";
+
+export const RUNNER_FAMILY_LABELS: Record<RunnerFamily, string> = {
+  "ge-proton": "GE-Proton",
+  "wine-ge": "Wine-GE",
+  "proton": "Proton",
+  "lutris-wine": "Lutris Wine",
+  "other": "Other",
+  "system-wine": "System Wine",
+};
+
 export interface WineVersionInfo {
   path: string;
   display_name: string;
+  family: RunnerFamily;
+  kind: "proton" | "wine" | null;
+  source: string;
+  version_timestamp: string | null;
+  supports_win32: boolean;
+  supports_win64: boolean;
+  size_bytes: number;
+  game_count: number;
+  managed: RunnerRecord | null;
 }
 
 export interface GeProtonRelease {
@@ -15,6 +105,83 @@ export interface GeProtonRelease {
   published_at: string;
   download_url: string;
   size_mb: number;
+  checksum_url: string | null;
+  /** Release notes (Markdown), empty when the source publishes none */
+  body: string;
+}
+
+export interface ReleaseNotes {
+  source: RunnerSource;
+  tag_name: string;
+  name: string;
+  published_at: string;
+  body: string;
+}
+
+export interface RunnerChangelog {
+  game: RunnerGame;
+  current_runner: string;
+  candidate_tag: string;
+  /** Newest first */
+  releases: ReleaseNotes[];
+}
+
+/** GitHub refused the request because the rate limit is used up */
+export interface RateLimit {
+  limit: number | null;
+  /** RFC3339 time the limit resets */
+  reset_at: string;
+  /** Whether a GitHub token from the settings was sent */
+  authenticated: boolean;
+}
+
+export interface ReleaseList {
+  releases: GeProtonRelease[];
+  /** Set when the releases are the last cached list because of the rate limit */
+  rate_limited: RateLimit | null;
+}
+
+export type RunnerSource =
+  | "ge-proton"
+  | "wine-ge"
+  | "kron4ek-wine"
+  | "proton-tkg"
+  | "lutris-wine";
+
+/** Lutris runner directory, or a Steam compatibilitytools.d directory */
+export type InstallDestination =
+  | { type: "lutris" }
+  | { type: "steam"; path: string };
+
+export interface RunnerSourceInfo {
+  id: RunnerSource;
+  display_name: string;
+  install_target: "proton" | "wine";
+}
+
+export type DownloadState =
+  | "queued"
+  | "downloading"
+  | "verifying"
+  | "extracting"
+  | "paused"
+  | "completed"
+  | "failed"
+  | "cancelled";
+
+export interface DownloadStatus {
+  id: number;
+  source: RunnerSource;
+  tag_name: string;
+  state: DownloadState;
+  downloaded: number;
+  total: number;
+  progress: number;
+  resumed_from: number;
+  speed_bytes_per_sec: number;
+  eta_seconds: number | null;
+  error: string | null;
+  installed_path: string | null;
 }
 
 class WineService {
@@ -25,6 +192,13 @@ class WineService {
     return invoke<WineVersionInfo[]>("get_available_wine_versions");
   }
 
+  /**
+   * Get installed Wine/Proton versions with disk usage and game counts (slower)
+   */
+  async getVersionsWithDetails(): Promise<WineVersionInfo[]> {
+    return invoke<WineVersionInfo[]>("get_wine_versions_with_details");
+  }
+
   /**
    * Set Lutris's global default Wine version for all new games
    */
@@ -40,24 +214,203 @@ class WineService {
   }
 
   /**
-   * Delete an installed Proton version
+   * Delete an installed Proton version (moves it to the trash)
+   * Games still using it are moved to the replacement first, deletion fails without one
    */
-  async deleteProtonVersion(path: string): Promise<void> {
-    return invoke("delete_proton_version", { path });
+  async deleteProtonVersion(
+    path: string,
+    replacement: string | null = null
+  ): Promise<RunnerDeleteReport> {
+    return invoke<RunnerDeleteReport>("delete_proton_version", { path, replacement });
   }
 
   /**
    * Fetch available GE-Proton releases from GitHub
    */
-  async fetchGeProtonReleases(): Promise<GeProtonRelease[]> {
-    return invoke<GeProtonRelease[]>("fetch_ge_proton_releases");
+  async fetchGeProtonReleases(): Promise<ReleaseList> {
+    return invoke<ReleaseList>("fetch_ge_proton_releases");
+  }
+
+  /**
+   * List the runner sources Rustris can download from
+   */
+  async getRunnerSources(): Promise<RunnerSourceInfo[]> {
+    return invoke<RunnerSourceInfo[]>("get_runner_sources");
+  }
+
+  /**
+   * Fetch available releases of a runner source
+   */
+  async fetchRunnerReleases(source: RunnerSource): Promise<ReleaseList> {
+    return invoke<ReleaseList>("fetch_runner_releases", { source });
+  }
+
+  /**
+   * Get the release notes between the runner a game uses and a newer release
+   */
+  async getRunnerChangelog(
+    gameSlug: string,
+    source: RunnerSource,
+    candidateTag: string
+  ): Promise<RunnerChangelog> {
+    return invoke<RunnerChangelog>("get_runner_changelog", {
+      gameSlug,
+      source,
+      candidateTag,
+    });
+  }
+
+  /**
+   * Download and install a runner release from any source
+   */
+  async downloadRunner(
+    source: RunnerSource,
+    release: GeProtonRelease,
+    destinations: InstallDestination[] | null = null
+  ): Promise<string> {
+    return invoke<string>("download_runner", { source, release, destinations });
+  }
+
+  /**
+   * Queue a runner download without waiting for it to finish
+   */
+  async queueRunnerDownload(
+    source: RunnerSource,
+    release: GeProtonRelease,
+    destinations: InstallDestination[] | null = null
+  ): Promise<number> {
+    return invoke<number>("queue_runner_download", { source, release, destinations });
+  }
+
+  /**
+   * List the Steam compatibilitytools.d directories runners can also be installed into
+   */
+  async getSteamCompatToolsDirs(): Promise<string[]> {
+    return invoke<string[]>("get_steam_compat_tools_dirs");
+  }
+
+  /**
+   * List queued, running and finished downloads
+   */
+  async listDownloads(): Promise<DownloadStatus[]> {
+    return invoke<DownloadStatus[]>("list_downloads");
+  }
+
+  async cancelDownload(id: number): Promise<void> {
+    return invoke("cancel_download", { id });
+  }
+
+  async pauseDownload(id: number): Promise<void> {
+    return invoke("pause_download", { id });
+  }
+
+  async resumeDownload(id: number): Promise<void> {
+    return invoke("resume_download", { id });
+  }
+
+  /**
+   * Set how many downloads may run at the same time
+   */
+  async setDownloadConcurrency(maxConcurrent: number): Promise<void> {
+    return invoke("set_download_concurrency", { maxConcurrent });
+  }
+
+  /**
+   * Import a runner from a local .tar.gz, .tar.xz or .tar.zst archive
+   * The target defaults to whatever the archive contains (Proton or Wine)
+   */
+  async importRunnerArchive(path: string, target?: "proton" | "wine"): Promise<string> {
+    return invoke<string>("import_runner_archive", { path, target: target ?? null });
+  }
+
+  /**
+   * List every runner installed by Rustris with its provenance
+   */
+  async getRunnerRegistry(): Promise<RunnerRecord[]> {
+    return invoke<RunnerRecord[]>("get_runner_registry");
+  }
+
+  /**
+   * Get every installed runner with the games that use it
+   */
+  async getRunnerUsage(): Promise<RunnerUsage[]> {
+    return invoke<RunnerUsage[]>("get_runner_usage");
+  }
+
+  /**
+   * Remove runners no game and not the Lutris default uses
+   * With dryRun nothing is deleted, the report lists what would be removed
+   */
+  async pruneUnusedRunners(dryRun: boolean): Promise<PruneReport> {
+    return invoke<PruneReport>("prune_unused_runners", { dryRun });
+  }
+
+  /**
+   * Apply the runner retention policy now (keep the newest N per source, plus pinned and in-use runners)
+   */
+  async applyRunnerRetention(dryRun: boolean): Promise<PruneReport> {
+    return invoke<PruneReport>("apply_runner_retention", { dryRun });
+  }
+
+  /**
+   * Pin a runner so retention never removes it
+   */
+  async pinRunner(path: string): Promise<void> {
+    return invoke("pin_runner", { path });
+  }
+
+  /**
+   * Unpin a runner
+   */
+  async unpinRunner(path: string): Promise<void> {
+    return invoke("unpin_runner", { path });
+  }
+
+  /**
+   * List runners in the trash
+   */
+  async listRunnerTrash(): Promise<TrashEntry[]> {
+    return invoke<TrashEntry[]>("list_runner_trash");
+  }
+
+  /**
+   * Move a trashed runner back to where it was installed
+   */
+  async restoreRunnerFromTrash(id: string): Promise<string> {
+    return invoke<string>("restore_runner_from_trash", { id });
+  }
+
+  /**
+   * Permanently delete everything in the trash
+   */
+  async emptyRunnerTrash(): Promise<number> {
+    return invoke<number>("empty_runner_trash");
+  }
+
+  /**
+   * Replace identical files across installed runners with hardlinks
+   */
+  async dedupeRunners(dryRun: boolean): Promise<DedupeReport> {
+    return invoke<DedupeReport>("dedupe_runners", { dryRun });
+  }
+
+  /**
+   * Give a runner its own copy of every file it shares with other runners
+   */
+  async undedupeRunner(path: string): Promise<UndedupeReport> {
+    return invoke<UndedupeReport>("undedupe_runner", { path });
   }
 
   /**
    * Download and install a GE-Proton version
    */
-  async downloadGeProton(tagName: string, downloadUrl: string): Promise<string> {
-    return invoke<string>("download_ge_proton", { tagName, downloadUrl });
+  async downloadGeProton(
+    tagName: string,
+    downloadUrl: string,
+    checksumUrl: string | null,
+    destinations: InstallDestination[] | null = null
+  ): Promise<string> {
+    return invoke<string>("download_ge_proton", { tagName, downloadUrl, checksumUrl, destinations });
   }
 }
 
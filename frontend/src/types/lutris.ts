@@ -5,6 +5,14 @@
 
 // ===== Game Data =====
 
+/**
+ * One environment variable from a Lutris config
+ */
+export interface EnvVar {
+  name: string;
+  value: string;
+}
+
 /**
  * Game data from Lutris database and config files
  * Returned by get_games() command
@@ -19,7 +27,7 @@ export interface GameData {
   executable: string | null;       // Full path to game executable
   wine_version: string | null;     // Wine/Proton version path
   wine_prefix: string | null;      // Wine prefix path
-  environment_vars: string | null; // Env vars (KEY=VALUE;KEY2=VALUE2)
+  environment_vars: EnvVar[];      // Env vars from system.env, in config order
   cover_url: string | null;        // Path to cover image
   debug_output: boolean;           // Debug logging enabled
 }
@@ -0,0 +1,489 @@
+/// Wine DLL overrides - the `wine.overrides` mapping of game configs and runners/wine.yml
+/// A game with its own overrides replaces the runner's as a whole, like Lutris merges them at launch
+use crate::lutris_cli;
+use crate::lutris_yaml::{self, YamlDocument};
+use crate::rustris_paths;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::path::PathBuf;
+
+/// How Wine loads a DLL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DllMode {
+    #[serde(rename = "native")]
+    Native,
+    #[serde(rename = "builtin")]
+    Builtin,
+    /// Native, falling back to Wine's builtin
+    #[serde(rename = "native,builtin")]
+    NativeBuiltin,
+    /// Builtin, falling back to native
+    #[serde(rename = "builtin,native")]
+    BuiltinNative,
+    /// Never load the DLL
+    #[serde(rename = "disabled")]
+    Disabled,
+}
+
+impl DllMode {
+    /// Value Lutris writes in the config
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DllMode::Native => "native",
+            DllMode::Builtin => "builtin",
+            DllMode::NativeBuiltin => "native,builtin",
+            DllMode::BuiltinNative => "builtin,native",
+            DllMode::Disabled => "disabled",
+        }
+    }
+
+    /// Parse a config value, including the WINEDLLOVERRIDES shorthands ("n,b", "d", "")
+    pub fn parse(value: &str) -> Option<Self> {
+        let expanded: Vec<&str> = value
+            .split(',')
+            .map(|part| match part.trim().to_lowercase().as_str() {
+                "n" | "native" => "native",
+                "b" | "builtin" => "builtin",
+                "" | "d" | "disabled" => "disabled",
+                _ => "?",
+            })
+            .collect();
+
+        match expanded.as_slice() {
+            ["native"] => Some(DllMode::Native),
+            ["builtin"] => Some(DllMode::Builtin),
+            ["native", "builtin"] => Some(DllMode::NativeBuiltin),
+            ["builtin", "native"] => Some(DllMode::BuiltinNative),
+            ["disabled"] => Some(DllMode::Disabled),
+            _ => None,
+        }
+    }
+}
+
+/// One DLL override
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DllOverride {
+    /// Module name without ".dll", e.g. "d3d9"
+    pub dll: String,
+    pub mode: DllMode,
+}
+
+/// Config level an effective override comes from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverrideSource {
+    Runner,
+    Game,
+}
+
+/// An override as Wine will see it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectiveDllOverride {
+    pub dll: String,
+    pub mode: DllMode,
+    pub source: OverrideSource,
+}
+
+/// Overrides set at one level next to what applies once the levels are merged
+#[derive(Debug, Serialize)]
+pub struct DllOverridesView {
+    /// Overrides set in the config of the requested scope
+    pub own: Vec<DllOverride>,
+    /// Overrides inherited from runners/wine.yml, empty for the runner scope
+    pub inherited: Vec<DllOverride>,
+    pub effective: Vec<EffectiveDllOverride>,
+}
+
+/// A ready-made set of overrides for a common setup
+#[derive(Debug, Clone, Serialize)]
+pub struct DllPreset {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub overrides: Vec<DllOverride>,
+}
+
+/// (id, name, description, overrides)
+type PresetEntry = (
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static [(&'static str, DllMode)],
+);
+
+const PRESETS: [PresetEntry; 5] = [
+    (
+        "mod-loader-dinput8",
+        "Mod loader (dinput8)",
+        "Script extenders and ASI loaders shipped as dinput8.dll",
+        &[("dinput8", DllMode::NativeBuiltin)],
+    ),
+    (
+        "asi-loader-winmm",
+        "ASI loader (winmm)",
+        "Ultimate ASI Loader installed as winmm.dll",
+        &[("winmm", DllMode::NativeBuiltin)],
+    ),
+    (
+        "reshade-d3d9",
+        "ReShade (Direct3D 9)",
+        "ReShade installed as d3d9.dll",
+        &[("d3d9", DllMode::NativeBuiltin)],
+    ),
+    (
+        "reshade-dxgi",
+        "ReShade (Direct3D 10/11/12)",
+        "ReShade installed as dxgi.dll",
+        &[("dxgi", DllMode::NativeBuiltin)],
+    ),
+    (
+        "reshade-opengl",
+        "ReShade (OpenGL)",
+        "ReShade installed as opengl32.dll",
+        &[("opengl32", DllMode::NativeBuiltin)],
+    ),
+];
+
+/// Which config file the overrides are read from and written to
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OverrideScope {
+    /// A game's own config in games/
+    Game { slug: String },
+    /// runners/wine.yml, inherited by every Wine game
+    Runner,
+}
+
+impl OverrideScope {
+    fn config_file(&self) -> Result<PathBuf, String> {
+        match self {
+            OverrideScope::Game { slug } => lutris_cli::game_config_file(slug),
+            OverrideScope::Runner => rustris_paths::lutris_wine_config()
+                .ok_or_else(|| "Could not get wine runner config path".to_string()),
+        }
+    }
+}
+
+pub fn presets() -> Vec<DllPreset> {
+    PRESETS
+        .iter()
+        .map(|(id, name, description, overrides)| DllPreset {
+            id,
+            name,
+            description,
+            overrides: overrides
+                .iter()
+                .map(|(dll, mode)| DllOverride {
+                    dll: dll.to_string(),
+                    mode: *mode,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Normalize a DLL name: lowercase, without ".dll"
+/// Wine also accepts other modules ("winemenubuilder.exe") and a leading '*' to match any path
+fn normalize_dll_name(name: &str) -> Result<String, String> {
+    let name = name.trim().to_lowercase();
+    let name = name.strip_suffix(".dll").unwrap_or(&name);
+    let module = name.strip_prefix('*').unwrap_or(name);
+
+    let valid = !module.is_empty()
+        && module
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !valid {
+        return Err(format!("Invalid DLL name '{}'", name));
+    }
+
+    Ok(name.to_string())
+}
+
+/// Get the overrides set in the `wine.overrides` section of a game or runner config
+/// None when the config has no overrides section, entries Wine would not understand are skipped
+pub fn overrides_from_config(config: &Value) -> Option<Vec<DllOverride>> {
+    let overrides = config
+        .get("wine")
+        .and_then(|wine| wine.get("overrides"))
+        .and_then(|o| o.as_mapping())?;
+
+    Some(
+        overrides
+            .iter()
+            .filter_map(|(dll, mode)| {
+                let mode = match mode {
+                    Value::Null => Some(DllMode::Disabled),
+                    _ => DllMode::parse(mode.as_str()?),
+                }?;
+                Some(DllOverride {
+                    dll: dll.as_str()?.to_string(),
+                    mode,
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Overrides Wine gets for a game
+/// Lutris merges the `wine` sections key by key, so a game's own overrides section
+/// replaces the runner's as a whole instead of being merged DLL by DLL
+pub fn merge_overrides(
+    runner: &[DllOverride],
+    game: Option<&[DllOverride]>,
+) -> Vec<EffectiveDllOverride> {
+    let (overrides, source) = match game {
+        Some(game) => (game, OverrideSource::Game),
+        None => (runner, OverrideSource::Runner),
+    };
+
+    overrides
+        .iter()
+        .map(|o| EffectiveDllOverride {
+            dll: o.dll.clone(),
+            mode: o.mode,
+            source,
+        })
+        .collect()
+}
+
+/// Overrides of runners/wine.yml
+fn runner_overrides() -> Vec<DllOverride> {
+    rustris_paths::lutris_wine_config()
+        .and_then(|path| overrides_from_config(&lutris_yaml::load_value(&path)))
+        .unwrap_or_default()
+}
+
+/// Raw `wine.overrides` entries of runners/wine.yml, including ones Rustris can't parse
+fn runner_override_entries() -> Vec<(String, Value)> {
+    let config = match rustris_paths::lutris_wine_config() {
+        Some(path) => lutris_yaml::load_value(&path),
+        None => return Vec::new(),
+    };
+
+    config
+        .get("wine")
+        .and_then(|wine| wine.get("overrides"))
+        .and_then(|o| o.as_mapping())
+        .map(|overrides| {
+            overrides
+                .iter()
+                .filter_map(|(dll, mode)| Some((dll.as_str()?.to_string(), mode.clone())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Key an override is stored under, reusing the existing spelling of the DLL
+fn config_key(existing: &[DllOverride], dll: &str) -> String {
+    existing
+        .iter()
+        .find(|o| o.dll.eq_ignore_ascii_case(dll))
+        .map(|o| o.dll.clone())
+        .unwrap_or_else(|| dll.to_string())
+}
+
+/// Set overrides in the config of `scope`, keeping everything else in the file as it is
+fn write_overrides(scope: &OverrideScope, overrides: &[DllOverride]) -> Result<(), String> {
+    let config_file = scope.config_file()?;
+    let existing = overrides_from_config(&lutris_yaml::load_value(&config_file));
+
+    // The game's first override section would hide the runner's, start it with a copy of
+    // the runner's entries as written, so values Rustris doesn't understand carry over too
+    let inherited = match (scope, &existing) {
+        (OverrideScope::Game { .. }, None) => runner_override_entries(),
+        _ => Vec::new(),
+    };
+    let existing = existing.unwrap_or_default();
+
+    lutris_yaml::edit_file(&config_file, |doc: &mut YamlDocument| {
+        for (dll, mode) in inherited
+            .iter()
+            .filter(|(dll, _)| !overrides.iter().any(|o| o.dll.eq_ignore_ascii_case(dll)))
+        {
+            doc.set(&["wine", "overrides", dll], mode.clone())?;
+        }
+        for o in overrides {
+            let key = config_key(&existing, &o.dll);
+            doc.set_str(&["wine", "overrides", &key], o.mode.as_str())?;
+        }
+        Ok(())
+    })
+}
+
+/// Get the DLL overrides of a game or the wine runner, with the merged result
+#[tauri::command]
+pub fn get_dll_overrides(scope: OverrideScope) -> Result<DllOverridesView, String> {
+    let own = overrides_from_config(&lutris_yaml::load_value(&scope.config_file()?));
+
+    let (inherited, effective) = match scope {
+        OverrideScope::Game { .. } => {
+            let inherited = runner_overrides();
+            let effective = merge_overrides(&inherited, own.as_deref());
+            (inherited, effective)
+        }
+        OverrideScope::Runner => (
+            Vec::new(),
+            merge_overrides(own.as_deref().unwrap_or_default(), None),
+        ),
+    };
+
+    Ok(DllOverridesView {
+        own: own.unwrap_or_default(),
+        inherited,
+        effective,
+    })
+}
+
+/// Add or change one DLL override
+#[tauri::command]
+pub fn set_dll_override(scope: OverrideScope, dll: String, mode: DllMode) -> Result<(), String> {
+    let dll = normalize_dll_name(&dll)?;
+    println!(
+        "Setting DLL override {}={} in {:?}",
+        dll,
+        mode.as_str(),
+        scope
+    );
+
+    write_overrides(&scope, &[DllOverride { dll, mode }])
+}
+
+/// Remove one DLL override
+/// Removing a game's last override drops its section, so the runner's overrides apply again
+#[tauri::command]
+pub fn remove_dll_override(scope: OverrideScope, dll: String) -> Result<(), String> {
+    let dll = normalize_dll_name(&dll)?;
+    println!("Removing DLL override {} from {:?}", dll, scope);
+
+    let config_file = scope.config_file()?;
+    let existing =
+        overrides_from_config(&lutris_yaml::load_value(&config_file)).unwrap_or_default();
+    let key = config_key(&existing, &dll);
+
+    lutris_yaml::edit_file(&config_file, |doc| {
+        if !doc.remove(&["wine", "overrides", &key])? {
+            return Err(format!("No override set for {}", dll));
+        }
+        // Decided on the raw mapping, entries Rustris can't parse still count
+        let emptied = doc
+            .value()?
+            .get("wine")
+            .and_then(|wine| wine.get("overrides"))
+            .and_then(|overrides| overrides.as_mapping())
+            .is_some_and(|overrides| overrides.is_empty());
+        if emptied {
+            doc.remove(&["wine", "overrides"])?;
+        }
+        Ok(())
+    })
+}
+
+/// List the built-in override presets
+#[tauri::command]
+pub fn list_dll_presets() -> Vec<DllPreset> {
+    presets()
+}
+
+/// Add the overrides of a preset, replacing existing overrides of the same DLLs
+#[tauri::command]
+pub fn apply_dll_preset(scope: OverrideScope, preset_id: String) -> Result<(), String> {
+    let preset = presets()
+        .into_iter()
+        .find(|p| p.id == preset_id)
+        .ok_or_else(|| format!("Unknown DLL override preset: {}", preset_id))?;
+    println!(
+        "Applying DLL override preset {} to {:?}",
+        preset.name, scope
+    );
+
+    write_overrides(&scope, &preset.overrides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_modes_and_names() {
+        assert_eq!(DllMode::parse("n,b"), Some(DllMode::NativeBuiltin));
+        assert_eq!(
+            DllMode::parse("native, builtin"),
+            Some(DllMode::NativeBuiltin)
+        );
+        assert_eq!(DllMode::parse("b"), Some(DllMode::Builtin));
+        assert_eq!(DllMode::parse(""), Some(DllMode::Disabled));
+        assert_eq!(DllMode::parse("disabled"), Some(DllMode::Disabled));
+        assert_eq!(DllMode::parse("n,n,b"), None);
+        assert_eq!(DllMode::parse("fast"), None);
+
+        assert_eq!(normalize_dll_name(" D3D9.dll ").unwrap(), "d3d9");
+        assert_eq!(
+            normalize_dll_name("winemenubuilder.exe").unwrap(),
+            "winemenubuilder.exe"
+        );
+        assert_eq!(normalize_dll_name("*xinput1_3").unwrap(), "*xinput1_3");
+        assert!(normalize_dll_name("d3d9=n").is_err());
+        assert!(normalize_dll_name("d3d9,dxgi").is_err());
+        assert!(normalize_dll_name("../d3d9").is_err());
+        assert!(normalize_dll_name("*").is_err());
+    }
+
+    #[test]
+    fn test_merge_overrides() {
+        let runner = overrides_from_config(
+            &serde_yaml::from_str(
+                "wine:\n  overrides:\n    winemenubuilder.exe: disabled\n    d3d9: builtin\n",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let game = overrides_from_config(
+            &serde_yaml::from_str(
+                "wine:\n  overrides:\n    D3D9: n,b\n    dinput8: native,builtin\n    xaudio2_7: bogus\n",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(game.len(), 2);
+        assert_eq!(
+            overrides_from_config(&serde_yaml::from_str("wine:\n  fsr: true\n").unwrap()),
+            None
+        );
+
+        let summary =
+            |effective: Vec<EffectiveDllOverride>| -> Vec<(String, DllMode, OverrideSource)> {
+                effective
+                    .into_iter()
+                    .map(|e| (e.dll, e.mode, e.source))
+                    .collect()
+            };
+
+        assert_eq!(
+            summary(merge_overrides(&runner, Some(&game))),
+            vec![
+                (
+                    "D3D9".to_string(),
+                    DllMode::NativeBuiltin,
+                    OverrideSource::Game
+                ),
+                (
+                    "dinput8".to_string(),
+                    DllMode::NativeBuiltin,
+                    OverrideSource::Game
+                ),
+            ]
+        );
+        assert_eq!(
+            summary(merge_overrides(&runner, None)),
+            vec![
+                (
+                    "winemenubuilder.exe".to_string(),
+                    DllMode::Disabled,
+                    OverrideSource::Runner
+                ),
+                ("d3d9".to_string(), DllMode::Builtin, OverrideSource::Runner),
+            ]
+        );
+    }
+}
@@ -0,0 +1,558 @@
+/// Runner download queue - limits how many downloads run at once and lets each one be
+/// paused, resumed or cancelled. Progress is reported through `download-progress` events.
+use crate::runner_sources::{InstallDestination, RunnerRelease, RunnerSource};
+use crate::rustris_settings;
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use tauri::Emitter;
+use tokio::sync::{oneshot, watch};
+
+/// Minimum time between two progress events for the same download
+const PROGRESS_EMIT_INTERVAL_MS: u128 = 250;
+
+/// Global download manager instance
+static DOWNLOAD_MANAGER: OnceLock<DownloadManager> = OnceLock::new();
+
+/// Get the global download manager
+pub fn get_download_manager() -> &'static DownloadManager {
+    DOWNLOAD_MANAGER.get_or_init(|| {
+        DownloadManager::new(rustris_settings::current_settings().downloads.max_concurrent)
+    })
+}
+
+/// Lifecycle of a queued download
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadState {
+    Queued,
+    Downloading,
+    Verifying,
+    Extracting,
+    Paused,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl DownloadState {
+    fn is_active(&self) -> bool {
+        matches!(
+            self,
+            DownloadState::Downloading | DownloadState::Verifying | DownloadState::Extracting
+        )
+    }
+
+    fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            DownloadState::Completed | DownloadState::Failed | DownloadState::Cancelled
+        )
+    }
+}
+
+/// What the user asked a running download to do
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DownloadControl {
+    Run,
+    Pause,
+    Cancel,
+}
+
+/// Snapshot of a download for the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadStatus {
+    pub id: u64,
+    pub source: RunnerSource,
+    pub tag_name: String,
+    pub state: DownloadState,
+    pub downloaded: u64,
+    pub total: u64,
+    pub progress: u32,
+    /// Byte offset the current attempt resumed from (0 for a fresh download)
+    pub resumed_from: u64,
+    pub speed_bytes_per_sec: u64,
+    pub eta_seconds: Option<u64>,
+    pub error: Option<String>,
+    pub installed_path: Option<String>,
+}
+
+struct QueueItem {
+    status: DownloadStatus,
+    release: RunnerRelease,
+    destinations: Vec<InstallDestination>,
+    control: watch::Sender<DownloadControl>,
+    waiters: Vec<oneshot::Sender<Result<String, String>>>,
+    app_handle: tauri::AppHandle,
+}
+
+struct QueueState {
+    items: Vec<QueueItem>,
+    next_id: u64,
+    max_concurrent: usize,
+}
+
+pub struct DownloadManager {
+    state: Mutex<QueueState>,
+}
+
+impl DownloadManager {
+    fn new(max_concurrent: usize) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                items: Vec::new(),
+                next_id: 1,
+                max_concurrent: max_concurrent.max(1),
+            }),
+        }
+    }
+
+    /// Add a release to the queue
+    /// Returns the download id and a receiver that resolves with the installed path
+    pub fn enqueue(
+        &'static self,
+        source: RunnerSource,
+        release: RunnerRelease,
+        destinations: Vec<InstallDestination>,
+        app_handle: tauri::AppHandle,
+    ) -> Result<(u64, oneshot::Receiver<Result<String, String>>), String> {
+        let (tx, rx) = oneshot::channel();
+
+        let id = {
+            let mut state = self.state.lock().unwrap();
+
+            let already_queued = state.items.iter().any(|item| {
+                item.status.source == source
+                    && item.status.tag_name == release.tag_name
+                    && !item.status.state.is_finished()
+            });
+            if already_queued {
+                return Err(format!("{} is already in the download queue", release.tag_name));
+            }
+
+            let id = state.next_id;
+            state.next_id += 1;
+
+            let (control, _) = watch::channel(DownloadControl::Run);
+            let status = DownloadStatus {
+                id,
+                source,
+                tag_name: release.tag_name.clone(),
+                state: DownloadState::Queued,
+                downloaded: 0,
+                total: 0,
+                progress: 0,
+                resumed_from: 0,
+                speed_bytes_per_sec: 0,
+                eta_seconds: None,
+                error: None,
+                installed_path: None,
+            };
+
+            emit_status(&app_handle, &status);
+            println!("Queued download #{}: {} {}", id, source.display_name(), release.tag_name);
+
+            state.items.push(QueueItem {
+                status,
+                release,
+                destinations,
+                control,
+                waiters: vec![tx],
+                app_handle,
+            });
+
+            id
+        };
+
+        self.pump();
+        Ok((id, rx))
+    }
+
+    /// Start queued downloads until the concurrency limit is reached
+    fn pump(&'static self) {
+        let mut state = self.state.lock().unwrap();
+        let mut active = state.items.iter().filter(|i| i.status.state.is_active()).count();
+        let max_concurrent = state.max_concurrent;
+
+        for item in state.items.iter_mut() {
+            if active >= max_concurrent {
+                break;
+            }
+            if item.status.state != DownloadState::Queued {
+                continue;
+            }
+
+            active += 1;
+            item.status.state = DownloadState::Downloading;
+            item.status.error = None;
+            item.control.send_replace(DownloadControl::Run);
+            emit_status(&item.app_handle, &item.status);
+
+            let id = item.status.id;
+            let source = item.status.source;
+            let release = item.release.clone();
+            let destinations = item.destinations.clone();
+            let control = item.control.subscribe();
+            let mut progress = DownloadProgress::new(id);
+            let app_handle = item.app_handle.clone();
+
+            tauri::async_runtime::spawn(async move {
+                let result = crate::proton_commands::install_runner(
+                    source,
+                    release,
+                    destinations,
+                    &mut progress,
+                    control,
+                )
+                .await;
+                let installed = result.as_ref().ok().cloned();
+                get_download_manager().finish(id, result);
+
+                // Old runners are only cleaned up once the new one is in place
+                if let Some(installed) = installed {
+                    crate::runner_retention::apply_after_install(&app_handle, installed).await;
+                }
+            });
+        }
+    }
+
+    /// Record the outcome of a download task and start the next queued item
+    fn finish(&'static self, id: u64, result: Result<String, String>) {
+        {
+            let mut state = self.state.lock().unwrap();
+            let item = match state.items.iter_mut().find(|i| i.status.id == id) {
+                Some(item) => item,
+                None => return,
+            };
+
+            let requested = *item.control.borrow();
+            item.status.speed_bytes_per_sec = 0;
+            item.status.eta_seconds = None;
+
+            match (result, requested) {
+                (Ok(path), _) => {
+                    item.status.state = DownloadState::Completed;
+                    item.status.progress = 100;
+                    item.status.installed_path = Some(path.clone());
+                    for waiter in item.waiters.drain(..) {
+                        let _ = waiter.send(Ok(path.clone()));
+                    }
+                }
+                // Keep the partial file and the waiters, resume picks both up again
+                (Err(_), DownloadControl::Pause) => {
+                    item.status.state = DownloadState::Paused;
+                }
+                (Err(_), DownloadControl::Cancel) => {
+                    item.status.state = DownloadState::Cancelled;
+                    remove_download_files(&item.release);
+                    for waiter in item.waiters.drain(..) {
+                        let _ = waiter.send(Err("Download cancelled".to_string()));
+                    }
+                }
+                (Err(e), DownloadControl::Run) => {
+                    item.status.state = DownloadState::Failed;
+                    item.status.error = Some(e.clone());
+                    for waiter in item.waiters.drain(..) {
+                        let _ = waiter.send(Err(e.clone()));
+                    }
+                }
+            }
+
+            println!("Download #{} finished: {:?}", id, item.status.state);
+            emit_status(&item.app_handle, &item.status);
+        }
+
+        self.pump();
+    }
+
+    /// Cancel a download, removing its partial file
+    pub fn cancel(&'static self, id: u64) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let item = state
+            .items
+            .iter_mut()
+            .find(|i| i.status.id == id)
+            .ok_or_else(|| format!("Download #{} not found", id))?;
+
+        match item.status.state {
+            s if s.is_active() => {
+                // The running task notices the signal and reports back through finish()
+                item.control.send_replace(DownloadControl::Cancel);
+            }
+            DownloadState::Queued | DownloadState::Paused => {
+                item.status.state = DownloadState::Cancelled;
+                remove_download_files(&item.release);
+                for waiter in item.waiters.drain(..) {
+                    let _ = waiter.send(Err("Download cancelled".to_string()));
+                }
+                emit_status(&item.app_handle, &item.status);
+            }
+            _ => return Err(format!("Download #{} has already finished", id)),
+        }
+
+        Ok(())
+    }
+
+    /// Pause a download, keeping its partial file for later
+    pub fn pause(&'static self, id: u64) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let item = state
+            .items
+            .iter_mut()
+            .find(|i| i.status.id == id)
+            .ok_or_else(|| format!("Download #{} not found", id))?;
+
+        match item.status.state {
+            s if s.is_active() => {
+                item.control.send_replace(DownloadControl::Pause);
+            }
+            DownloadState::Queued => {
+                item.status.state = DownloadState::Paused;
+                emit_status(&item.app_handle, &item.status);
+            }
+            _ => return Err(format!("Download #{} cannot be paused", id)),
+        }
+
+        Ok(())
+    }
+
+    /// Put a paused or failed download back into the queue
+    pub fn resume(&'static self, id: u64) -> Result<(), String> {
+        {
+            let mut state = self.state.lock().unwrap();
+            let item = state
+                .items
+                .iter_mut()
+                .find(|i| i.status.id == id)
+                .ok_or_else(|| format!("Download #{} not found", id))?;
+
+            match item.status.state {
+                DownloadState::Paused | DownloadState::Failed => {
+                    item.status.state = DownloadState::Queued;
+                    item.status.error = None;
+                    item.control.send_replace(DownloadControl::Run);
+                    emit_status(&item.app_handle, &item.status);
+                }
+                _ => return Err(format!("Download #{} is not paused", id)),
+            }
+        }
+
+        self.pump();
+        Ok(())
+    }
+
+    /// Change how many downloads may run at the same time
+    pub fn set_max_concurrent(&'static self, max_concurrent: usize) {
+        self.state.lock().unwrap().max_concurrent = max_concurrent.max(1);
+        self.pump();
+    }
+
+    /// Get every download in queue order
+    pub fn list(&self) -> Vec<DownloadStatus> {
+        let state = self.state.lock().unwrap();
+        state.items.iter().map(|i| i.status.clone()).collect()
+    }
+
+    /// Forget completed, failed and cancelled downloads
+    pub fn clear_finished(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.items.retain(|i| !i.status.state.is_finished());
+    }
+
+    /// Update the status of a running download and notify the frontend
+    fn update(&self, id: u64, emit: bool, apply: impl FnOnce(&mut DownloadStatus)) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(item) = state.items.iter_mut().find(|i| i.status.id == id) {
+            apply(&mut item.status);
+            if emit {
+                emit_status(&item.app_handle, &item.status);
+            }
+        }
+    }
+}
+
+/// Progress reporter handed to the download pipeline for one queue item
+/// Tracks transfer speed and ETA and throttles `download-progress` events
+pub struct DownloadProgress {
+    id: u64,
+    started: Instant,
+    started_at_bytes: u64,
+    last_emit: Option<Instant>,
+}
+
+impl DownloadProgress {
+    fn new(id: u64) -> Self {
+        Self {
+            id,
+            started: Instant::now(),
+            started_at_bytes: 0,
+            last_emit: None,
+        }
+    }
+
+    /// A new transfer attempt started at `resumed_from` bytes
+    pub fn start_attempt(&mut self, resumed_from: u64, total: u64) {
+        self.started = Instant::now();
+        self.started_at_bytes = resumed_from;
+        self.last_emit = None;
+
+        get_download_manager().update(self.id, true, |status| {
+            status.state = DownloadState::Downloading;
+            status.resumed_from = resumed_from;
+            status.downloaded = resumed_from;
+            status.total = total;
+            status.progress = percent(resumed_from, total);
+        });
+    }
+
+    /// Bytes received so far (including the resumed offset)
+    pub fn report(&mut self, downloaded: u64, total: u64) {
+        let now = Instant::now();
+        let emit = self
+            .last_emit
+            .map(|last| now.duration_since(last).as_millis() >= PROGRESS_EMIT_INTERVAL_MS)
+            .unwrap_or(true)
+            || (total > 0 && downloaded >= total);
+
+        if !emit {
+            return;
+        }
+        self.last_emit = Some(now);
+
+        let elapsed = now.duration_since(self.started).as_secs_f64();
+        let transferred = downloaded.saturating_sub(self.started_at_bytes);
+        let speed = if elapsed > 0.0 {
+            (transferred as f64 / elapsed) as u64
+        } else {
+            0
+        };
+        let eta = if speed > 0 && total > downloaded {
+            Some((total - downloaded) / speed)
+        } else {
+            None
+        };
+
+        get_download_manager().update(self.id, true, |status| {
+            status.downloaded = downloaded;
+            status.total = total;
+            status.progress = percent(downloaded, total);
+            status.speed_bytes_per_sec = speed;
+            status.eta_seconds = eta;
+        });
+    }
+
+    /// Move the download into another pipeline stage (verifying, extracting)
+    pub fn set_state(&mut self, state: DownloadState) {
+        get_download_manager().update(self.id, true, |status| {
+            status.state = state;
+            status.speed_bytes_per_sec = 0;
+            status.eta_seconds = None;
+            if state == DownloadState::Extracting {
+                status.progress = 100;
+            }
+        });
+    }
+}
+
+fn percent(downloaded: u64, total: u64) -> u32 {
+    if total > 0 {
+        (downloaded as f64 / total as f64 * 100.0) as u32
+    } else {
+        0
+    }
+}
+
+/// Emit a `download-progress` event
+/// Keeps the original fields (tag_name, downloaded, total, progress, extracting) for existing listeners
+fn emit_status(app_handle: &tauri::AppHandle, status: &DownloadStatus) {
+    let _ = app_handle.emit("download-progress", serde_json::json!({
+        "id": status.id,
+        "source": status.source,
+        "tag_name": status.tag_name,
+        "state": status.state,
+        "downloaded": status.downloaded,
+        "total": status.total,
+        "progress": status.progress,
+        "resumed_from": status.resumed_from,
+        "speed_bytes_per_sec": status.speed_bytes_per_sec,
+        "eta_seconds": status.eta_seconds,
+        "extracting": status.state == DownloadState::Extracting,
+        "error": status.error,
+    }));
+}
+
+/// Delete the partial and completed archive of a cancelled download
+fn remove_download_files(release: &RunnerRelease) {
+    if let Some((archive_path, partial_path)) = crate::proton_commands::archive_paths(release) {
+        let _ = std::fs::remove_file(crate::proton_commands::resume_validator_path(&partial_path));
+        let _ = std::fs::remove_file(partial_path);
+        let _ = std::fs::remove_file(archive_path);
+    }
+}
+
+/// Wait until the user pauses or cancels the download
+pub async fn wait_for_stop(control: &mut watch::Receiver<DownloadControl>) -> DownloadControl {
+    loop {
+        let current = *control.borrow_and_update();
+        if current != DownloadControl::Run {
+            return current;
+        }
+        if control.changed().await.is_err() {
+            return DownloadControl::Cancel;
+        }
+    }
+}
+
+/// Queue a runner download without waiting for it, returns the download id
+#[tauri::command]
+pub fn queue_runner_download(
+    source: RunnerSource,
+    release: RunnerRelease,
+    destinations: Option<Vec<InstallDestination>>,
+    app_handle: tauri::AppHandle,
+) -> Result<u64, String> {
+    get_download_manager()
+        .enqueue(source, release, destinations.unwrap_or_else(|| vec![InstallDestination::Lutris]), app_handle)
+        .map(|(id, _)| id)
+}
+
+/// List queued, running and finished downloads
+#[tauri::command]
+pub fn list_downloads() -> Vec<DownloadStatus> {
+    get_download_manager().list()
+}
+
+/// Cancel a download and delete its partial file
+#[tauri::command]
+pub fn cancel_download(id: u64) -> Result<(), String> {
+    get_download_manager().cancel(id)
+}
+
+/// Pause a download, it can be resumed later from where it stopped
+#[tauri::command]
+pub fn pause_download(id: u64) -> Result<(), String> {
+    get_download_manager().pause(id)
+}
+
+/// Resume a paused or failed download
+#[tauri::command]
+pub fn resume_download(id: u64) -> Result<(), String> {
+    get_download_manager().resume(id)
+}
+
+/// Remove finished downloads from the list
+#[tauri::command]
+pub fn clear_finished_downloads() {
+    get_download_manager().clear_finished()
+}
+
+/// Set how many downloads may run at the same time and persist it
+#[tauri::command]
+pub fn set_download_concurrency(max_concurrent: usize) -> Result<(), String> {
+    rustris_settings::update_settings(|settings| {
+        settings.downloads.max_concurrent = max_concurrent.max(1);
+    })?;
+
+    get_download_manager().set_max_concurrent(max_concurrent);
+    Ok(())
+}
@@ -0,0 +1,276 @@
+/// Effective Lutris config of a game - system.yml, runners/<runner>.yml and the game config
+/// merged the way Lutris does at launch, recording which level every value comes from
+use crate::lutris_db::LutrisDatabase;
+use crate::lutris_yaml;
+use crate::rustris_paths;
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use std::path::PathBuf;
+
+/// Config level a value is set at, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigLevel {
+    /// system.yml
+    System,
+    /// runners/<runner>.yml
+    Runner,
+    /// games/<config>.yml
+    Game,
+}
+
+/// One key of a merged section
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResolvedOption {
+    pub key: String,
+    pub value: Value,
+    /// Level the value comes from, the highest contributing level for merged mappings
+    pub level: ConfigLevel,
+    /// Lower levels that set the key too, their values are not used
+    pub overridden: Vec<ConfigLevel>,
+    /// Provenance of every entry of a mapping merged key by key, such as `system.env`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<ResolvedOption>,
+}
+
+/// A game's config after merging every level
+#[derive(Debug, Serialize)]
+pub struct EffectiveConfig {
+    pub game_slug: String,
+    /// Runner the game uses, names the runner section (e.g. "wine")
+    pub runner: Option<String>,
+    /// `game:` section, only games set it
+    pub game: Vec<ResolvedOption>,
+    /// Runner section: runners/<runner>.yml, then the game config
+    pub runner_options: Vec<ResolvedOption>,
+    /// `system:` section: system.yml, runners/<runner>.yml, then the game config
+    pub system: Vec<ResolvedOption>,
+}
+
+fn section<'a>(config: &'a Value, name: &str) -> Option<&'a Mapping> {
+    config.get(name)?.as_mapping()
+}
+
+/// Keys of the `system` section Lutris merges key by key across levels (`merge_to_system_config`)
+const MERGED_SYSTEM_KEYS: [&str; 1] = ["env"];
+
+/// Merge one level's mapping into `merged`, a key set again replaces the lower value
+/// Keys listed in `merged_keys` are mappings merged entry by entry instead
+fn merge_level(
+    merged: &mut Vec<ResolvedOption>,
+    level: ConfigLevel,
+    section: &Mapping,
+    merged_keys: &[&str],
+) {
+    for (key, value) in section.iter() {
+        let key = match key.as_str() {
+            Some(k) => k,
+            None => continue,
+        };
+
+        let existing = merged.iter().position(|o| o.key == key);
+        let option = match existing {
+            Some(index) => &mut merged[index],
+            None => {
+                merged.push(ResolvedOption {
+                    key: key.to_string(),
+                    value: Value::Null,
+                    level,
+                    overridden: Vec::new(),
+                    entries: Vec::new(),
+                });
+                merged.last_mut().unwrap()
+            }
+        };
+
+        match value.as_mapping().filter(|_| merged_keys.contains(&key)) {
+            Some(mapping) => {
+                merge_level(&mut option.entries, level, mapping, &[]);
+                option.level = level;
+                option.value = Value::Mapping(
+                    option
+                        .entries
+                        .iter()
+                        .map(|e| (Value::String(e.key.clone()), e.value.clone()))
+                        .collect(),
+                );
+            }
+            None => {
+                if existing.is_some() {
+                    option.overridden.push(option.level);
+                }
+                option.level = level;
+                option.value = value.clone();
+                option.entries.clear();
+            }
+        }
+    }
+}
+
+/// Merge one section across levels, lowest first
+/// Like Lutris, a key set at a higher level replaces the lower value whole, except for
+/// `merged_keys` - mappings such as `overrides` in the runner section are not merged
+fn merge_section(
+    levels: &[(ConfigLevel, Option<&Mapping>)],
+    merged_keys: &[&str],
+) -> Vec<ResolvedOption> {
+    let mut merged: Vec<ResolvedOption> = Vec::new();
+
+    for (level, section) in levels {
+        if let Some(section) = section {
+            merge_level(&mut merged, *level, section, merged_keys);
+        }
+    }
+
+    merged
+}
+
+/// Merge the three config levels of a game
+pub fn resolve(
+    game_slug: &str,
+    runner: Option<&str>,
+    system_config: &Value,
+    runner_config: &Value,
+    game_config: &Value,
+) -> EffectiveConfig {
+    let runner_options = match runner {
+        Some(runner) => merge_section(
+            &[
+                (ConfigLevel::Runner, section(runner_config, runner)),
+                (ConfigLevel::Game, section(game_config, runner)),
+            ],
+            &[],
+        ),
+        None => Vec::new(),
+    };
+
+    EffectiveConfig {
+        game_slug: game_slug.to_string(),
+        runner: runner.map(|r| r.to_string()),
+        game: merge_section(&[(ConfigLevel::Game, section(game_config, "game"))], &[]),
+        runner_options,
+        system: merge_section(
+            &[
+                (ConfigLevel::System, section(system_config, "system")),
+                (ConfigLevel::Runner, section(runner_config, "system")),
+                (ConfigLevel::Game, section(game_config, "system")),
+            ],
+            &MERGED_SYSTEM_KEYS,
+        ),
+    }
+}
+
+/// Load and merge the config levels of an installed game
+pub fn effective_config(slug: &str) -> Result<EffectiveConfig, String> {
+    let game = LutrisDatabase::new()?.get_game(slug)?;
+    let configpath = game
+        .configpath
+        .ok_or_else(|| format!("Game '{}' has no config path in database", slug))?;
+
+    let load = |path: Option<PathBuf>| {
+        path.map(|p| lutris_yaml::load_value(&p))
+            .unwrap_or_default()
+    };
+    let runner = game.runner.filter(|r| !r.is_empty());
+
+    Ok(resolve(
+        slug,
+        runner.as_deref(),
+        &load(rustris_paths::lutris_system_config()),
+        &load(
+            runner
+                .as_deref()
+                .and_then(rustris_paths::lutris_runner_config),
+        ),
+        &load(rustris_paths::lutris_game_config(&configpath)),
+    ))
+}
+
+/// Get a game's effective config and where each value comes from
+#[tauri::command]
+pub fn get_effective_config(slug: String) -> Result<EffectiveConfig, String> {
+    effective_config(&slug)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml::from_str(text).unwrap()
+    }
+
+    fn summary(options: &[ResolvedOption]) -> Vec<(&str, ConfigLevel, Vec<ConfigLevel>)> {
+        options
+            .iter()
+            .map(|o| (o.key.as_str(), o.level, o.overridden.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve() {
+        let system =
+            yaml("system:\n  gamemode: true\n  mangohud: false\n  env:\n    DXVK_HUD: fps\n");
+        let runner =
+            yaml("system:\n  mangohud: true\n  env:\n    DXVK_HUD: full\n    DXVK_ASYNC: 1\nwine:\n  version: wine-ge-8-26\n  dxvk: true\n");
+        let game = yaml(
+            "\
+game:
+  exe: drive_c/Game/game.exe
+  prefix: /games/prefix
+system:
+  env:
+    WINEDLLOVERRIDES: d3d11=n,b
+  mangohud: false
+wine:
+  version: GE-Proton10-27
+",
+        );
+
+        let config = resolve("game", Some("wine"), &system, &runner, &game);
+
+        assert_eq!(
+            summary(&config.system),
+            vec![
+                ("gamemode", ConfigLevel::System, vec![]),
+                (
+                    "mangohud",
+                    ConfigLevel::Game,
+                    vec![ConfigLevel::System, ConfigLevel::Runner]
+                ),
+                ("env", ConfigLevel::Game, vec![]),
+            ]
+        );
+        // env is merged variable by variable, system.yml and runner variables stay
+        assert_eq!(
+            summary(&config.system[2].entries),
+            vec![
+                ("DXVK_HUD", ConfigLevel::Runner, vec![ConfigLevel::System]),
+                ("DXVK_ASYNC", ConfigLevel::Runner, vec![]),
+                ("WINEDLLOVERRIDES", ConfigLevel::Game, vec![]),
+            ]
+        );
+        assert_eq!(
+            config.system[2].value,
+            yaml("DXVK_HUD: full\nDXVK_ASYNC: 1\nWINEDLLOVERRIDES: d3d11=n,b")
+        );
+
+        assert_eq!(
+            summary(&config.runner_options),
+            vec![
+                ("version", ConfigLevel::Game, vec![ConfigLevel::Runner]),
+                ("dxvk", ConfigLevel::Runner, vec![]),
+            ]
+        );
+        assert_eq!(
+            config.runner_options[0].value,
+            Value::String("GE-Proton10-27".to_string())
+        );
+        assert_eq!(config.game.len(), 2);
+
+        let no_runner = resolve("game", None, &system, &runner, &Value::Null);
+        assert!(no_runner.runner_options.is_empty());
+        assert!(no_runner.game.is_empty());
+        assert_eq!(no_runner.system.len(), 3);
+    }
+}
@@ -0,0 +1,368 @@
+/// Environment variables from the `system.env` section of Lutris configs
+/// Editable per game, for the wine runner (runners/wine.yml) and system-wide (system.yml)
+use crate::lutris_cli;
+use crate::lutris_yaml::{self, YamlDocument};
+use crate::rustris_paths;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::path::PathBuf;
+
+/// One environment variable, in the order it appears in the config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvVar {
+    pub name: String,
+    pub value: String,
+}
+
+/// Which config file the variables are read from and written to
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EnvScope {
+    /// A game's own config in games/
+    Game { slug: String },
+    /// runners/wine.yml, inherited by every Wine game
+    Runner,
+    /// system.yml, inherited by every game
+    System,
+}
+
+impl EnvScope {
+    fn config_file(&self) -> Result<PathBuf, String> {
+        match self {
+            EnvScope::Game { slug } => lutris_cli::game_config_file(slug),
+            EnvScope::Runner => rustris_paths::lutris_wine_config()
+                .ok_or_else(|| "Could not get wine runner config path".to_string()),
+            EnvScope::System => rustris_paths::lutris_system_config()
+                .ok_or_else(|| "Could not get Lutris system config path".to_string()),
+        }
+    }
+}
+
+/// Scalars as the string the game sees, hand-edited configs often write `DXVK_ASYNC: 1`
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Null => Some(String::new()),
+        _ => None,
+    }
+}
+
+/// Get the variables of an `env` mapping, keeping their order
+pub fn env_vars_from_mapping(env: &Mapping) -> Vec<EnvVar> {
+    env.iter()
+        .filter_map(|(name, value)| {
+            Some(EnvVar {
+                name: scalar_to_string(name)?,
+                value: scalar_to_string(value)?,
+            })
+        })
+        .collect()
+}
+
+/// Get the variables set in the `system.env` section of a config
+pub fn env_vars_from_config(config: &Value) -> Vec<EnvVar> {
+    config
+        .get("system")
+        .and_then(|system| system.get("env"))
+        .and_then(|env| env.as_mapping())
+        .map(env_vars_from_mapping)
+        .unwrap_or_default()
+}
+
+/// Names the shell could export: letters, digits and underscores, not starting with a digit
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if !valid {
+        return Err(format!("Invalid environment variable name '{}'", name));
+    }
+
+    Ok(())
+}
+
+/// Put the variables in the order of `names`, which must name every variable exactly once
+fn reordered(vars: &[EnvVar], names: &[String]) -> Result<Vec<EnvVar>, String> {
+    if names.len() != vars.len() {
+        return Err(format!(
+            "Expected {} environment variables, got {}",
+            vars.len(),
+            names.len()
+        ));
+    }
+
+    let mut ordered = Vec::with_capacity(vars.len());
+    for name in names {
+        let var = vars
+            .iter()
+            .find(|v| &v.name == name)
+            .ok_or_else(|| format!("Environment variable {} is not set", name))?;
+        if ordered.iter().any(|v: &EnvVar| &v.name == name) {
+            return Err(format!("Environment variable {} is listed twice", name));
+        }
+        ordered.push(var.clone());
+    }
+
+    Ok(ordered)
+}
+
+/// Rename and/or change a variable in place
+/// Renaming only rewrites the key, the value keeps its spelling and comments stay
+fn write_update(
+    current: &[EnvVar],
+    doc: &mut YamlDocument,
+    name: &str,
+    var: &EnvVar,
+) -> Result<(), String> {
+    let existing = current
+        .iter()
+        .find(|v| v.name == name)
+        .ok_or_else(|| format!("Environment variable {} is not set", name))?;
+
+    if var.name != name {
+        if current.iter().any(|v| v.name == var.name) {
+            return Err(format!("Environment variable {} is already set", var.name));
+        }
+        doc.rename(&["system", "env", name], &var.name)?;
+    }
+
+    if existing.value != var.value {
+        doc.set_str(&["system", "env", &var.name], &var.value)?;
+    }
+
+    Ok(())
+}
+
+/// Move the variables into the order of `names`, each with the comments above it
+/// Entries that are not scalars are not listed as variables, they keep their place
+fn write_order(current: &[EnvVar], doc: &mut YamlDocument, names: &[String]) -> Result<(), String> {
+    let ordered = reordered(current, names)?;
+    if ordered == current {
+        return Ok(());
+    }
+
+    let config = doc.value()?;
+    let all_keys: Vec<String> = config
+        .get("system")
+        .and_then(|system| system.get("env"))
+        .and_then(|env| env.as_mapping())
+        .map(|env| env.keys().filter_map(scalar_to_string).collect())
+        .unwrap_or_default();
+
+    let mut listed = ordered.iter();
+    let keys: Vec<&str> = all_keys
+        .iter()
+        .map(|key| {
+            if current.iter().any(|v| &v.name == key) {
+                listed.next().map(|v| v.name.as_str()).unwrap_or(key)
+            } else {
+                key.as_str()
+            }
+        })
+        .collect();
+    doc.reorder(&["system", "env"], &keys)
+}
+
+/// Apply an edit to the config of `scope` and return the variables it ends up with
+fn edit_env_vars(
+    scope: &EnvScope,
+    edit: impl FnOnce(&[EnvVar], &mut YamlDocument) -> Result<(), String>,
+) -> Result<Vec<EnvVar>, String> {
+    let config_file = scope.config_file()?;
+    let mut result = Vec::new();
+
+    // Read the variables from the document being edited, not a separate read of the file
+    lutris_yaml::edit_file(&config_file, |doc| {
+        let current = env_vars_from_config(&doc.value()?);
+        edit(&current, doc)?;
+        result = env_vars_from_config(&doc.value()?);
+        Ok(())
+    })?;
+
+    Ok(result)
+}
+
+/// Get the environment variables of a game, the wine runner or the system config
+#[tauri::command]
+pub fn get_env_vars(scope: EnvScope) -> Result<Vec<EnvVar>, String> {
+    let config_file = scope.config_file()?;
+    Ok(env_vars_from_config(&lutris_yaml::load_value(&config_file)))
+}
+
+/// Add a variable after the existing ones
+#[tauri::command]
+pub fn add_env_var(scope: EnvScope, var: EnvVar) -> Result<Vec<EnvVar>, String> {
+    println!("Adding environment variable {} to {:?}", var.name, scope);
+    validate_name(&var.name)?;
+
+    edit_env_vars(&scope, |current, doc| {
+        if current.iter().any(|v| v.name == var.name) {
+            return Err(format!("Environment variable {} is already set", var.name));
+        }
+        doc.set_str(&["system", "env", &var.name], &var.value)
+    })
+}
+
+/// Change the value and/or name of a variable, it keeps its position
+#[tauri::command]
+pub fn update_env_var(scope: EnvScope, name: String, var: EnvVar) -> Result<Vec<EnvVar>, String> {
+    println!("Updating environment variable {} in {:?}", name, scope);
+    validate_name(&var.name)?;
+
+    edit_env_vars(&scope, |current, doc| {
+        write_update(current, doc, &name, &var)
+    })
+}
+
+/// Remove a variable
+#[tauri::command]
+pub fn remove_env_var(scope: EnvScope, name: String) -> Result<Vec<EnvVar>, String> {
+    println!("Removing environment variable {} from {:?}", name, scope);
+
+    edit_env_vars(&scope, |_, doc| {
+        if !doc.remove(&["system", "env", &name])? {
+            return Err(format!("Environment variable {} is not set", name));
+        }
+        Ok(())
+    })
+}
+
+/// Put the variables in a new order, `names` lists every variable once
+#[tauri::command]
+pub fn reorder_env_vars(scope: EnvScope, names: Vec<String>) -> Result<Vec<EnvVar>, String> {
+    println!("Reordering environment variables in {:?}", scope);
+
+    edit_env_vars(&scope, |current, doc| write_order(current, doc, &names))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAME_CONFIG: &str = "\
+game:
+  exe: drive_c/Game/game.exe
+system:
+  env:
+    WINEDLLOVERRIDES: d3d11=n,b;dxgi=n,b
+    DXVK_ASYNC: 1
+    PULSE_LATENCY_MSEC: '60' # crackling audio
+  prefer_system_libs: true
+wine:
+  version: wine-ge-8-26
+";
+
+    fn names(vars: &[EnvVar]) -> Vec<&str> {
+        vars.iter().map(|v| v.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_env_vars_from_config() {
+        let vars = env_vars_from_config(&serde_yaml::from_str(GAME_CONFIG).unwrap());
+
+        assert_eq!(
+            names(&vars),
+            vec!["WINEDLLOVERRIDES", "DXVK_ASYNC", "PULSE_LATENCY_MSEC"]
+        );
+        assert_eq!(vars[0].value, "d3d11=n,b;dxgi=n,b");
+        assert_eq!(vars[1].value, "1");
+        assert_eq!(vars[2].value, "60");
+    }
+
+    #[test]
+    fn test_reorder_and_rename() {
+        let mut doc = YamlDocument::parse(GAME_CONFIG).unwrap();
+        let vars = env_vars_from_config(&serde_yaml::from_str(GAME_CONFIG).unwrap());
+
+        let order = vec![
+            "PULSE_LATENCY_MSEC".to_string(),
+            "DXVK_ASYNC".to_string(),
+            "WINEDLLOVERRIDES".to_string(),
+        ];
+        write_order(&vars, &mut doc, &order).unwrap();
+
+        let renamed = EnvVar {
+            name: "PULSE_LATENCY".to_string(),
+            value: "60".to_string(),
+        };
+        write_update(&vars, &mut doc, "PULSE_LATENCY_MSEC", &renamed).unwrap();
+
+        assert_eq!(
+            doc.to_string(),
+            "\
+game:
+  exe: drive_c/Game/game.exe
+system:
+  env:
+    PULSE_LATENCY: '60' # crackling audio
+    DXVK_ASYNC: 1
+    WINEDLLOVERRIDES: d3d11=n,b;dxgi=n,b
+  prefer_system_libs: true
+wine:
+  version: wine-ge-8-26
+"
+        );
+
+        let vars = env_vars_from_config(&serde_yaml::from_str(&doc.to_string()).unwrap());
+        let taken = EnvVar {
+            name: "DXVK_ASYNC".to_string(),
+            value: "1".to_string(),
+        };
+        assert!(write_update(&vars, &mut doc, "PULSE_LATENCY", &taken).is_err());
+        assert!(write_update(&vars, &mut doc, "MISSING", &taken).is_err());
+
+        assert!(reordered(&vars, &order[..2]).is_err());
+        assert!(reordered(
+            &vars,
+            &[order[1].clone(), order[1].clone(), order[2].clone()]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_reorder_keeps_non_scalar_values() {
+        let config = "\
+system:
+  env:
+    DXVK_HUD: fps
+    LIST:
+    - a
+    - b
+    DXVK_ASYNC: 1
+";
+        let mut doc = YamlDocument::parse(config).unwrap();
+        let vars = env_vars_from_config(&doc.value().unwrap());
+        assert_eq!(names(&vars), ["DXVK_HUD", "DXVK_ASYNC"]);
+
+        let order = vec!["DXVK_ASYNC".to_string(), "DXVK_HUD".to_string()];
+        write_order(&vars, &mut doc, &order).unwrap();
+
+        assert_eq!(
+            doc.to_string(),
+            "\
+system:
+  env:
+    DXVK_ASYNC: 1
+    LIST:
+    - a
+    - b
+    DXVK_HUD: fps
+"
+        );
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("DXVK_HUD").is_ok());
+        assert!(validate_name("_private1").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("1ST").is_err());
+        assert!(validate_name("A=B").is_err());
+        assert!(validate_name("WITH SPACE").is_err());
+    }
+}
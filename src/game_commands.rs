@@ -1,3 +1,4 @@
+use crate::effective_config::{self, EffectiveConfig};
 use crate::lutris_cli::{self, GameData};
 use crate::rustris_paths;
 use crate::game_log_buffer::LogBufferManager;
@@ -167,6 +168,8 @@ struct DiagnosisData {
 struct LutrisConfigs {
     game_config: Option<serde_yaml::Value>,
     runner_config: Option<serde_yaml::Value>,
+    /// Merged config, with the level every value comes from
+    effective_config: Option<EffectiveConfig>,
 }
 
 #[tauri::command]
@@ -244,6 +247,7 @@ pub async fn save_game_log(slug: String) -> Result<String, String> {
             lutris_configs: LutrisConfigs {
                 game_config: game_config_content,
                 runner_config: runner_config_content,
+                effective_config: effective_config::effective_config(&slug).ok(),
             },
         },
     };
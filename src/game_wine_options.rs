@@ -0,0 +1,309 @@
+/// Per-game Wine/Proton runner options - the `wine:` section of a Lutris game config
+/// Options a game does not set are inherited from runners/wine.yml, then from Lutris's own defaults
+use crate::lutris_cli;
+use crate::lutris_yaml::{self, YamlDocument};
+use crate::rustris_paths;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+/// Declares every option with the key Lutris stores it under in the `wine:` section,
+/// so reading, merging and writing always cover the same fields
+macro_rules! wine_options {
+    ($($(#[doc = $doc:literal])* $field:ident: $ty:ty => $key:literal,)*) => {
+        /// Wine runner options, None means the option is not set at that level
+        #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+        #[serde(default)]
+        pub struct GameWineOptions {
+            $($(#[doc = $doc])* pub $field: Option<$ty>,)*
+        }
+
+        impl GameWineOptions {
+            /// Read the options of a `wine:` section, values that can't be read are left unset
+            fn from_section(section: &Value) -> Self {
+                GameWineOptions {
+                    $($field: section.get($key).and_then(<$ty as OptionValue>::read),)*
+                }
+            }
+
+            /// Fill every unset option from `fallback`
+            pub fn or(&self, fallback: &GameWineOptions) -> GameWineOptions {
+                GameWineOptions {
+                    $($field: self.$field.clone().or_else(|| fallback.$field.clone()),)*
+                }
+            }
+
+            /// Config key and YAML value of every option, None for options that are not set
+            fn entries(&self) -> Vec<(&'static str, Option<Value>)> {
+                vec![$(($key, self.$field.as_ref().map(OptionValue::to_yaml)),)*]
+            }
+        }
+    };
+}
+
+wine_options! {
+    dxvk: bool => "dxvk",
+    dxvk_version: String => "dxvk_version",
+    vkd3d: bool => "vkd3d",
+    vkd3d_version: String => "vkd3d_version",
+    d3d_extras: bool => "d3d_extras",
+    d3d_extras_version: String => "d3d_extras_version",
+    dxvk_nvapi: bool => "dxvk_nvapi",
+    dxvk_nvapi_version: String => "dxvk_nvapi_version",
+    esync: bool => "esync",
+    fsync: bool => "fsync",
+    fsr: bool => "fsr",
+    battleye: bool => "battleye",
+    eac: bool => "eac",
+    /// Run the game in a Wine virtual desktop
+    virtual_desktop: bool => "Desktop",
+    /// Virtual desktop size, e.g. "1920x1080"
+    desktop_resolution: String => "WineDesktop",
+    /// auto, alsa, pulse or oss
+    audio_driver: String => "Audio",
+    /// WINEDEBUG channels, "-all" turns Wine's logging off
+    show_debug: String => "show_debug",
+}
+
+/// A game's own options next to what it inherits
+#[derive(Debug, Serialize)]
+pub struct GameWineOptionsView {
+    /// Options set in the game's config
+    pub game: GameWineOptions,
+    /// What each unset option falls back to: runners/wine.yml, then Lutris's defaults
+    pub defaults: GameWineOptions,
+}
+
+const AUDIO_DRIVERS: [&str; 4] = ["auto", "alsa", "pulse", "oss"];
+
+/// Lutris's built-in defaults for the wine runner
+fn lutris_defaults() -> GameWineOptions {
+    GameWineOptions {
+        dxvk: Some(true),
+        vkd3d: Some(true),
+        d3d_extras: Some(true),
+        dxvk_nvapi: Some(true),
+        esync: Some(true),
+        fsync: Some(true),
+        fsr: Some(true),
+        battleye: Some(true),
+        eac: Some(true),
+        virtual_desktop: Some(false),
+        audio_driver: Some("auto".to_string()),
+        show_debug: Some("-all".to_string()),
+        ..Default::default()
+    }
+}
+
+/// A value type options are stored as
+trait OptionValue: Sized {
+    fn read(value: &Value) -> Option<Self>;
+    fn to_yaml(&self) -> Value;
+}
+
+/// Accepts the forms hand-edited configs use ("true", "no", `Desktop: 1`)
+impl OptionValue for bool {
+    fn read(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(*b),
+            Value::Number(n) => match n.as_u64()? {
+                1 => Some(true),
+                0 => Some(false),
+                _ => None,
+            },
+            Value::String(s) => match s.to_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => Some(true),
+                "false" | "no" | "off" | "0" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn to_yaml(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl OptionValue for String {
+    fn read(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            Value::Bool(b) => Some(b.to_string()),
+            _ => None,
+        }
+    }
+
+    fn to_yaml(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+/// Get the options set in the `wine:` section of a game or runner config
+pub fn options_from_config(config: &Value) -> GameWineOptions {
+    config
+        .get("wine")
+        .map(GameWineOptions::from_section)
+        .unwrap_or_default()
+}
+
+impl GameWineOptions {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(driver) = &self.audio_driver {
+            if !AUDIO_DRIVERS.contains(&driver.as_str()) {
+                return Err(format!(
+                    "Unknown audio driver '{}', expected one of: {}",
+                    driver,
+                    AUDIO_DRIVERS.join(", ")
+                ));
+            }
+        }
+
+        if let Some(resolution) = &self.desktop_resolution {
+            let valid = resolution
+                .split_once('x')
+                .map(|(w, h)| w.parse::<u32>().is_ok() && h.parse::<u32>().is_ok())
+                .unwrap_or(false);
+            if !valid {
+                return Err(format!(
+                    "Invalid virtual desktop resolution '{}', expected WIDTHxHEIGHT",
+                    resolution
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Options every game inherits: runners/wine.yml over Lutris's defaults
+pub fn default_wine_options() -> GameWineOptions {
+    let runner_config = rustris_paths::lutris_wine_config()
+        .map(|path| lutris_yaml::load_value(&path))
+        .unwrap_or_default();
+
+    options_from_config(&runner_config).or(&lutris_defaults())
+}
+
+/// Write the options that changed to the `wine:` section, removing the ones that are no longer set
+/// Unchanged options keep their spelling, and values Rustris could not read are left alone
+fn write_options(doc: &mut YamlDocument, options: &GameWineOptions) -> Result<(), String> {
+    let current = options_from_config(&doc.value()?).entries();
+
+    for ((key, value), (_, current)) in options.entries().into_iter().zip(current) {
+        if value == current {
+            continue;
+        }
+
+        match value {
+            Some(value) => doc.set(&["wine", key], value)?,
+            None => {
+                doc.remove(&["wine", key])?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Get a game's Wine/Proton options and the defaults it inherits
+#[tauri::command]
+pub fn get_game_wine_options(slug: String) -> Result<GameWineOptionsView, String> {
+    let config = lutris_yaml::load_value(&lutris_cli::game_config_file(&slug)?);
+
+    Ok(GameWineOptionsView {
+        game: options_from_config(&config),
+        defaults: default_wine_options(),
+    })
+}
+
+/// Replace a game's Wine/Proton options, unset options go back to inheriting the default
+#[tauri::command]
+pub fn set_game_wine_options(slug: String, options: GameWineOptions) -> Result<(), String> {
+    println!("Updating wine options for game: {}", slug);
+
+    options.validate()?;
+    let config_file = lutris_cli::game_config_file(&slug)?;
+    lutris_yaml::edit_file(&config_file, |doc| write_options(doc, &options))?;
+
+    println!("   Wine options updated successfully!");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_from_config() {
+        let config: Value = serde_yaml::from_str(
+            "\
+game:
+  exe: game.exe
+wine:
+  Audio: pulse
+  Desktop: true
+  WineDesktop: 1280x720
+  dxvk: 'false'
+  esync: true
+  show_debug: -all
+  version: lutris-GE-Proton8-26-x86_64
+",
+        )
+        .unwrap();
+
+        let options = options_from_config(&config);
+        assert_eq!(options.dxvk, Some(false));
+        assert_eq!(options.esync, Some(true));
+        assert_eq!(options.fsync, None);
+        assert_eq!(options.virtual_desktop, Some(true));
+        assert_eq!(options.desktop_resolution.as_deref(), Some("1280x720"));
+        assert_eq!(options.audio_driver.as_deref(), Some("pulse"));
+
+        let effective = options.or(&lutris_defaults());
+        assert_eq!(effective.dxvk, Some(false));
+        assert_eq!(effective.fsync, Some(true));
+        assert_eq!(effective.dxvk_version, None);
+    }
+
+    #[test]
+    fn test_write_options() {
+        let mut doc =
+            YamlDocument::parse("wine:\n  dxvk: false\n  version: wine-ge-8-26\n").unwrap();
+        let options = GameWineOptions {
+            fsr: Some(false),
+            virtual_desktop: Some(true),
+            desktop_resolution: Some("1920x1080".to_string()),
+            ..Default::default()
+        };
+        write_options(&mut doc, &options).unwrap();
+
+        assert_eq!(
+            doc.to_string(),
+            "wine:\n  version: wine-ge-8-26\n  fsr: false\n  Desktop: true\n  WineDesktop: 1920x1080\n"
+        );
+
+        // Values that can't be read stay, readable ones keep their spelling unless they change
+        let mut doc = YamlDocument::parse(
+            "wine:\n  Desktop: 1\n  dxvk: null\n  esync: 'yes'\n  show_debug: 0\n  fsync: true\n",
+        )
+        .unwrap();
+        let mut options = options_from_config(&doc.value().unwrap());
+        assert_eq!(options.virtual_desktop, Some(true));
+        assert_eq!(options.dxvk, None);
+        assert_eq!(options.show_debug.as_deref(), Some("0"));
+
+        options.fsync = Some(false);
+        write_options(&mut doc, &options).unwrap();
+        assert_eq!(
+            doc.to_string(),
+            "wine:\n  Desktop: 1\n  dxvk: null\n  esync: 'yes'\n  show_debug: 0\n  fsync: false\n"
+        );
+
+        let invalid = GameWineOptions {
+            desktop_resolution: Some("1920".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
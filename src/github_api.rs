@@ -0,0 +1,368 @@
+/// GitHub REST API client - conditional requests with an on-disk ETag cache, Link pagination
+/// and rate-limit handling that falls back to the last cached response
+use crate::http_client;
+use crate::rustris_paths;
+use crate::rustris_settings;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, ETAG, IF_NONE_MATCH, LINK};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// GitHub refused the request because the rate limit is used up
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimit {
+    /// Requests allowed per hour (60 without a token)
+    pub limit: Option<u64>,
+    /// RFC3339 time the limit resets
+    pub reset_at: String,
+    /// Whether a personal access token was sent
+    pub authenticated: bool,
+}
+
+impl RateLimit {
+    pub fn message(&self) -> String {
+        format!("GitHub API rate limit reached, resets at {}", self.reset_at)
+    }
+}
+
+/// All items of a paginated list endpoint
+pub struct Listing {
+    pub items: Vec<serde_json::Value>,
+    /// Set when the items come from the cache because the rate limit was hit
+    pub rate_limited: Option<RateLimit>,
+}
+
+/// One cached page of a list endpoint
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedPage {
+    url: String,
+    etag: Option<String>,
+    /// URL of the next page from the Link header
+    next: Option<String>,
+    items: Vec<serde_json::Value>,
+}
+
+enum PageResult {
+    Page(CachedPage),
+    RateLimited(RateLimit),
+}
+
+fn cache_file(url: &str) -> Option<PathBuf> {
+    use sha2::{Digest, Sha256};
+
+    let key = format!("{:x}", Sha256::digest(url.as_bytes()));
+    rustris_paths::rustris_github_cache_dir().map(|d| d.join(format!("{}.json", key)))
+}
+
+fn load_page(url: &str) -> Option<CachedPage> {
+    let content = fs::read_to_string(cache_file(url)?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_page(page: &CachedPage) {
+    let result = cache_file(&page.url)
+        .ok_or_else(|| "Could not get GitHub cache path".to_string())
+        .and_then(|file| {
+            if let Some(parent) = file.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let json = serde_json::to_string(page).map_err(|e| e.to_string())?;
+
+            let tmp_file = file.with_extension("json.tmp");
+            fs::write(&tmp_file, json).map_err(|e| e.to_string())?;
+            fs::rename(&tmp_file, &file).map_err(|e| e.to_string())
+        });
+
+    if let Err(e) = result {
+        println!("Warning: could not cache GitHub response: {}", e);
+    }
+}
+
+/// Get the URL of the next page from a Link header
+/// e.g. `<https://api.github.com/...&page=2>; rel="next", <...&page=5>; rel="last"`
+pub fn next_page_url(link: &str) -> Option<String> {
+    link.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        let is_next = params
+            .split(';')
+            .any(|p| p.trim().replace(' ', "") == "rel=\"next\"");
+
+        if is_next {
+            Some(
+                url.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string(),
+            )
+        } else {
+            None
+        }
+    })
+}
+
+/// Read the rate limit headers of a refused request
+/// Returns None when the 403/429 is not about the rate limit
+fn rate_limit_from_headers(headers: &HeaderMap, authenticated: bool) -> Option<RateLimit> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<i64>().ok())
+    };
+
+    let reset = if header("x-ratelimit-remaining") == Some(0) {
+        header("x-ratelimit-reset")
+    } else {
+        // Secondary rate limit, GitHub only says how long to wait
+        header("retry-after").map(|secs| chrono::Utc::now().timestamp() + secs)
+    }?;
+
+    Some(RateLimit {
+        limit: header("x-ratelimit-limit").map(|l| l as u64),
+        reset_at: chrono::DateTime::<chrono::Utc>::from_timestamp(reset, 0)?.to_rfc3339(),
+        authenticated,
+    })
+}
+
+/// Host the GitHub token is always sent to
+const GITHUB_API_HOST: &str = "api.github.com";
+
+/// HTTP client for the GitHub API and the token to authenticate with
+struct GitHubClient {
+    client: reqwest::Client,
+    /// `Authorization` header value built from the token in the settings
+    authorization: Option<HeaderValue>,
+    /// Custom API base URL the token may also be sent to, when the settings opt in
+    token_origin: Option<Url>,
+}
+
+impl GitHubClient {
+    fn new() -> Result<Self, String> {
+        let settings = rustris_settings::current_settings();
+        let token = settings.github.token.filter(|t| !t.trim().is_empty());
+
+        let authorization = match &token {
+            Some(token) => Some(
+                format!("Bearer {}", token.trim())
+                    .parse()
+                    .map_err(|_| "Invalid GitHub token in settings".to_string())?,
+            ),
+            None => None,
+        };
+        let token_origin = if settings.github.send_token_to_custom_api {
+            Url::parse(&http_client::github_api_url()).ok()
+        } else {
+            None
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, "application/vnd.github+json".parse().unwrap());
+
+        let client = http_client::api_client_builder()?
+            .default_headers(headers)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            authorization,
+            token_origin,
+        })
+    }
+
+    /// Token header for a request to `url`, only for api.github.com over HTTPS
+    /// or the configured API when the settings opt in
+    fn authorization_for(&self, url: &str) -> Option<&HeaderValue> {
+        let url = Url::parse(url).ok()?;
+        let trusted = (url.scheme() == "https" && url.host_str() == Some(GITHUB_API_HOST))
+            || self
+                .token_origin
+                .as_ref()
+                .is_some_and(|origin| origin.origin() == url.origin());
+
+        self.authorization.as_ref().filter(|_| trusted)
+    }
+}
+
+/// Whether two URLs share scheme, host and port
+fn same_origin(a: &str, b: &str) -> bool {
+    match (Url::parse(a), Url::parse(b)) {
+        (Ok(a), Ok(b)) => a.origin() == b.origin(),
+        _ => false,
+    }
+}
+
+/// Fetch one page, revalidating the cached copy with If-None-Match
+async fn fetch_page(
+    client: &GitHubClient,
+    url: &str,
+    authenticated: bool,
+) -> Result<PageResult, String> {
+    let cached = load_page(url);
+
+    let mut request = client.client.get(url);
+    if let Some(authorization) = client.authorization_for(url) {
+        request = request.header(AUTHORIZATION, authorization.clone());
+    }
+    if let Some(etag) = cached.as_ref().and_then(|c| c.etag.as_ref()) {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+
+    let response = http_client::send_with_retry(request)
+        .await
+        .map_err(|e| format!("GitHub request failed: {}", e))?;
+    let status = response.status();
+
+    if status == StatusCode::NOT_MODIFIED {
+        if let Some(page) = cached {
+            return Ok(PageResult::Page(page));
+        }
+    }
+
+    if status == StatusCode::FORBIDDEN || status == StatusCode::TOO_MANY_REQUESTS {
+        if let Some(rate_limit) = rate_limit_from_headers(response.headers(), authenticated) {
+            return Ok(PageResult::RateLimited(rate_limit));
+        }
+    }
+
+    if !status.is_success() {
+        return Err(format!("GitHub API returned status: {}", status));
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+    };
+    let etag = header(ETAG);
+    // Pagination never leaves the API the listing started on
+    let next = header(LINK)
+        .and_then(|link| next_page_url(&link))
+        .filter(|next| {
+            let same = same_origin(next, url);
+            if !same {
+                println!("   Warning: ignoring next page on another origin: {}", next);
+            }
+            same
+        });
+
+    let items: Vec<serde_json::Value> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    let page = CachedPage {
+        url: url.to_string(),
+        etag,
+        next,
+        items,
+    };
+    save_page(&page);
+
+    Ok(PageResult::Page(page))
+}
+
+/// Items of every cached page, following the cached next links from `url`
+fn cached_listing(url: &str) -> Vec<serde_json::Value> {
+    let mut items = Vec::new();
+    let mut next = Some(url.to_string());
+
+    while let Some(page) = next.and_then(|url| load_page(&url)) {
+        items.extend(page.items);
+        next = page.next;
+    }
+
+    items
+}
+
+/// Fetch every page of a list endpoint such as `/repos/{owner}/{repo}/releases`
+pub async fn get_all_pages(path: &str) -> Result<Listing, String> {
+    let first_url = format!("{}{}?per_page=100", http_client::github_api_url(), path);
+    let client = GitHubClient::new()?;
+    let authenticated = client.authorization_for(&first_url).is_some();
+
+    let mut items = Vec::new();
+    let mut next = Some(first_url.clone());
+
+    while let Some(url) = next {
+        match fetch_page(&client, &url, authenticated).await? {
+            PageResult::Page(page) => {
+                items.extend(page.items);
+                next = page.next;
+            }
+            PageResult::RateLimited(rate_limit) => {
+                println!("   {}, using cached response", rate_limit.message());
+                return Ok(Listing {
+                    items: cached_listing(&first_url),
+                    rate_limited: Some(rate_limit),
+                });
+            }
+        }
+    }
+
+    Ok(Listing {
+        items,
+        rate_limited: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_page_url() {
+        let link = r#"<https://api.github.com/repositories/1/releases?per_page=100&page=2>; rel="next", <https://api.github.com/repositories/1/releases?per_page=100&page=4>; rel="last""#;
+        assert_eq!(
+            next_page_url(link).as_deref(),
+            Some("https://api.github.com/repositories/1/releases?per_page=100&page=2")
+        );
+
+        let last_page = r#"<https://api.github.com/repositories/1/releases?per_page=100&page=1>; rel="prev", <https://api.github.com/repositories/1/releases?per_page=100&page=1>; rel="first""#;
+        assert_eq!(next_page_url(last_page), None);
+    }
+
+    #[test]
+    fn test_token_scope() {
+        let client = GitHubClient {
+            client: reqwest::Client::new(),
+            authorization: Some(HeaderValue::from_static("Bearer token")),
+            token_origin: None,
+        };
+        assert!(client
+            .authorization_for("https://api.github.com/repos/a/b/releases")
+            .is_some());
+        assert!(client
+            .authorization_for("http://api.github.com/repos/a/b/releases")
+            .is_none());
+        assert!(client
+            .authorization_for("https://api.github.com.evil.example/")
+            .is_none());
+        assert!(client
+            .authorization_for("https://ghe.example/api/v3/repos")
+            .is_none());
+
+        let enterprise = GitHubClient {
+            token_origin: Url::parse("https://ghe.example/api/v3").ok(),
+            ..client
+        };
+        assert!(enterprise
+            .authorization_for("https://ghe.example/api/v3/repos")
+            .is_some());
+        assert!(enterprise
+            .authorization_for("https://ghe.example:8443/api/v3/repos")
+            .is_none());
+
+        assert!(same_origin(
+            "https://api.github.com/repos/a/b/releases",
+            "https://api.github.com/repositories/1/releases?page=2"
+        ));
+        assert!(!same_origin(
+            "https://api.github.com/repos/a/b/releases",
+            "https://evil.example/releases?page=2"
+        ));
+    }
+}
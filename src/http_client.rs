@@ -0,0 +1,137 @@
+/// Shared HTTP setup - API base URLs, proxy, timeouts and retries used by every request Rustris makes
+/// Proxies from HTTP_PROXY/HTTPS_PROXY/NO_PROXY are picked up by reqwest unless settings name one
+use crate::rustris_settings::{self, NetworkSettings};
+use reqwest::{Client, ClientBuilder, RequestBuilder, Response};
+use std::time::Duration;
+
+/// Longest wait between two retries
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Environment variable first, then the setting, without a trailing slash
+fn base_url(env_var: &str, configured: String) -> String {
+    std::env::var(env_var)
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or(configured)
+        .trim()
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Base URL of the GitHub REST API, e.g. "https://api.github.com"
+pub fn github_api_url() -> String {
+    base_url(
+        "RUSTRIS_GITHUB_API_URL",
+        rustris_settings::current_settings().network.github_api_url,
+    )
+}
+
+/// Base URL of the Lutris API, e.g. "https://lutris.net/api"
+pub fn lutris_api_url() -> String {
+    base_url(
+        "RUSTRIS_LUTRIS_API_URL",
+        rustris_settings::current_settings().network.lutris_api_url,
+    )
+}
+
+fn base_builder(network: &NetworkSettings) -> Result<ClientBuilder, String> {
+    let mut builder = Client::builder()
+        .user_agent("Rustris")
+        .connect_timeout(Duration::from_secs(network.connect_timeout_secs));
+
+    if let Some(proxy) = network.proxy.as_ref().filter(|p| !p.trim().is_empty()) {
+        let proxy = reqwest::Proxy::all(proxy.trim())
+            .map_err(|e| format!("Invalid proxy {}: {}", proxy, e))?;
+        builder = builder.proxy(proxy);
+    }
+
+    Ok(builder)
+}
+
+/// Client builder for API requests, each request must complete within the timeout
+pub fn api_client_builder() -> Result<ClientBuilder, String> {
+    let network = rustris_settings::current_settings().network;
+    Ok(base_builder(&network)?.timeout(Duration::from_secs(network.timeout_secs)))
+}
+
+/// Client for API requests
+pub fn api_client() -> Result<Client, String> {
+    api_client_builder()?
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Client for runner downloads
+/// Downloads take as long as they take, only a stalled connection times out
+pub fn download_client() -> Result<Client, String> {
+    let network = rustris_settings::current_settings().network;
+
+    base_builder(&network)?
+        .read_timeout(Duration::from_secs(network.timeout_secs))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// How many times a failed request is retried
+pub fn max_retries() -> u32 {
+    rustris_settings::current_settings().network.max_retries
+}
+
+/// Wait before retry number `retry` (starting at 1): 0.5s, 1s, 2s, ... up to 30s
+pub fn backoff_delay(retry: u32) -> Duration {
+    let delay =
+        Duration::from_millis(500).saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)));
+    delay.min(MAX_BACKOFF)
+}
+
+/// Send a request, retrying connection failures, timeouts and server errors with backoff
+pub async fn send_with_retry(request: RequestBuilder) -> Result<Response, String> {
+    let max_retries = max_retries();
+    let mut retry = 0;
+
+    loop {
+        // Requests with a streaming body cannot be cloned, those get a single attempt
+        let attempt = match request.try_clone() {
+            Some(attempt) => attempt,
+            None => return request.send().await.map_err(|e| e.to_string()),
+        };
+
+        let failure = match attempt.send().await {
+            Ok(response) if response.status().is_server_error() && retry < max_retries => {
+                format!("HTTP status {}", response.status())
+            }
+            Ok(response) => return Ok(response),
+            Err(e)
+                if (e.is_connect() || e.is_timeout() || e.is_request()) && retry < max_retries =>
+            {
+                e.to_string()
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+
+        retry += 1;
+        let delay = backoff_delay(retry);
+        println!(
+            "   Request failed ({}), retrying in {:.1}s (retry {}/{})",
+            failure,
+            delay.as_secs_f64(),
+            retry,
+            max_retries
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(1), Duration::from_millis(500));
+        assert_eq!(backoff_delay(2), Duration::from_secs(1));
+        assert_eq!(backoff_delay(4), Duration::from_secs(4));
+        assert_eq!(backoff_delay(10), MAX_BACKOFF);
+        assert_eq!(backoff_delay(100), MAX_BACKOFF);
+    }
+}
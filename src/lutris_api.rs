@@ -1,16 +1,17 @@
+use crate::http_client;
 use serde_json::Value;
 
 #[tauri::command]
 pub async fn search_lutris_games(search: Option<String>) -> Result<Value, String> {
-    let base_url = "https://lutris.net/api/games";
+    let base_url = format!("{}/games", http_client::lutris_api_url());
 
-    let mut url = reqwest::Url::parse(base_url)
+    let mut url = reqwest::Url::parse(&base_url)
         .map_err(|e| format!("Invalid URL: {}", e))?;
     if let Some(search_term) = search {
         url.query_pairs_mut().append_pair("search", &search_term);
     }
 
-    let response = reqwest::get(url.as_str())
+    let response = http_client::send_with_retry(http_client::api_client()?.get(url))
         .await
         .map_err(|e| format!("Failed to fetch from Lutris API: {}", e))?;
 
@@ -28,9 +29,9 @@ pub async fn search_lutris_games(search: Option<String>) -> Result<Value, String
 
 #[tauri::command]
 pub async fn get_lutris_installers(game_slug: String) -> Result<Value, String> {
-    let url = format!("https://lutris.net/api/games/{}/installers", game_slug);
+    let url = format!("{}/games/{}/installers", http_client::lutris_api_url(), game_slug);
 
-    let response = reqwest::get(&url)
+    let response = http_client::send_with_retry(http_client::api_client()?.get(&url))
         .await
         .map_err(|e| format!("Failed to fetch installers: {}", e))?;
 
@@ -53,9 +54,9 @@ pub async fn get_lutris_installers(game_slug: String) -> Result<Value, String> {
 
 #[tauri::command]
 pub async fn get_lutris_installer(installer_id: i64) -> Result<Value, String> {
-    let url = format!("https://lutris.net/api/installers/{}", installer_id);
+    let url = format!("{}/installers/{}", http_client::lutris_api_url(), installer_id);
 
-    let response = reqwest::get(&url)
+    let response = http_client::send_with_retry(http_client::api_client()?.get(&url))
         .await
         .map_err(|e| format!("Failed to fetch installer: {}", e))?;
 
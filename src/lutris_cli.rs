@@ -1,8 +1,9 @@
+use crate::env_vars::{self, EnvVar};
 use crate::lutris_db::LutrisDatabase;
 use crate::lutris_util::LutrisConfig;
+use crate::lutris_yaml::{self, YamlDocument};
 use crate::rustris_paths;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::OnceLock;
@@ -224,7 +225,7 @@ pub struct GameData {
     pub executable: Option<String>,
     pub wine_version: Option<String>,
     pub wine_prefix: Option<String>,
-    pub environment_vars: Option<String>,
+    pub environment_vars: Vec<EnvVar>,
 
     // UI/metadata
     pub cover_url: Option<String>,
@@ -246,7 +247,7 @@ struct GameConfig {
 
 #[derive(Debug, Deserialize, Serialize)]
 struct SystemConfig {
-    env: Option<HashMap<String, String>>,
+    env: Option<serde_yaml::Mapping>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -262,8 +263,6 @@ struct WineConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     fsr: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    overrides: Option<serde_yaml::Value>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     show_debug: Option<String>,
 }
 
@@ -323,45 +322,77 @@ pub async fn launch_game_via_lutris_with_capture(
     Ok(())
 }
 
+/// Resolve a `wine.version` name to a runner folder
+/// Lutris looks the name up in both its proton and wine runner directories
+pub(crate) fn resolve_wine_version_name(version_name: &str) -> Option<PathBuf> {
+    [rustris_paths::lutris_proton_dir(), rustris_paths::lutris_wine_dir()]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join(version_name))
+        .find(|path| path.exists())
+}
+
+/// Get the runner folder from a `custom_wine_path` executable
+/// e.g. ".../GE-Proton10-27/proton" or ".../wine-ge-8-26/bin/wine" -> the runner folder
+pub(crate) fn runner_dir_from_executable(executable: &str) -> PathBuf {
+    let path = PathBuf::from(executable);
+
+    for suffix in ["proton", "bin/wine", "bin/wine64", "files/bin/wine", "files/bin/wine64"] {
+        if path.ends_with(suffix) {
+            let mut dir = path.clone();
+            for _ in suffix.split('/') {
+                dir.pop();
+            }
+            return dir;
+        }
+    }
+
+    path
+}
+
 /// Load wine/config data from a Lutris config file
 /// Returns (wine_version, wine_prefix, environment_vars, executable)
-fn load_config_from_path(
+pub(crate) fn load_config_from_path(
     configpath: &str,
     directory: &Option<String>,
-) -> (Option<String>, Option<String>, Option<String>, Option<String>) {
+) -> (Option<String>, Option<String>, Vec<EnvVar>, Option<String>) {
     let config_file = match rustris_paths::lutris_game_config(configpath) {
         Some(f) => f,
-        None => return (None, None, None, None),
+        None => return (None, None, Vec::new(), None),
     };
 
     if !config_file.exists() {
-        return (None, None, None, None);
+        return (None, None, Vec::new(), None);
     }
 
     let yaml_content = match fs::read_to_string(&config_file) {
         Ok(c) => c,
-        Err(_) => return (None, None, None, None),
+        Err(_) => return (None, None, Vec::new(), None),
     };
 
     let config: LutrisConfigFile = match serde_yaml::from_str(&yaml_content) {
         Ok(c) => c,
-        Err(_) => return (None, None, None, None),
+        Err(_) => return (None, None, Vec::new(), None),
     };
 
     let mut wine_version = None;
     let mut wine_prefix = None;
-    let mut environment_vars = None;
+    let mut environment_vars = Vec::new();
     let mut executable = None;
 
     // Extract wine version from version field
     if let Some(wine_cfg) = config.wine {
         if let Some(version_name) = wine_cfg.version {
-            // Resolve version name to full path by checking Lutris proton directory
-            if let Some(proton_dir) = rustris_paths::lutris_proton_dir() {
-                let version_path = proton_dir.join(&version_name);
-                if version_path.exists() {
-                    wine_version = Some(version_path.to_string_lossy().to_string());
-                }
+            // Resolve version name to full path by checking the Lutris runner directories
+            if let Some(version_path) = resolve_wine_version_name(&version_name) {
+                wine_version = Some(version_path.to_string_lossy().to_string());
+            }
+        }
+
+        // Older configs point straight at the executable instead
+        if wine_version.is_none() {
+            if let Some(custom_path) = wine_cfg.custom_wine_path {
+                wine_version = Some(runner_dir_from_executable(&custom_path).to_string_lossy().to_string());
             }
         }
     }
@@ -394,10 +425,7 @@ fn load_config_from_path(
     // Extract environment variables
     if let Some(system_cfg) = config.system {
         if let Some(env) = system_cfg.env {
-            let env_string: Vec<String> = env.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
-            if !env_string.is_empty() {
-                environment_vars = Some(env_string.join(";"));
-            }
+            environment_vars = env_vars::env_vars_from_mapping(&env);
         }
     }
 
@@ -429,7 +457,7 @@ pub async fn list_games_with_data() -> Result<Vec<GameData>, String> {
                 if let Some(ref configpath) = g.configpath {
                     load_config_from_path(configpath, &g.directory)
                 } else {
-                    (None, None, None, g.executable.clone())
+                    (None, None, Vec::new(), g.executable.clone())
                 };
 
             // Find cover art
@@ -466,48 +494,84 @@ pub async fn list_games_with_data() -> Result<Vec<GameData>, String> {
     Ok(games)
 }
 
-/// Get Lutris's default Wine version from runners/wine.yml
-/// Returns the full path to the wine version directory
-pub fn get_lutris_default_wine_version() -> Option<String> {
-    let wine_config = rustris_paths::lutris_wine_config()?;
+/// Runner folder a `wine:` section points at, `what` names the config in errors
+/// None when the section sets no runner, an error when the one it sets can't be found
+fn wine_section_runner(config: &serde_yaml::Value, what: &str) -> Result<Option<String>, String> {
+    let wine_section = match config.get("wine") {
+        Some(section) => section,
+        None => return Ok(None),
+    };
 
-    if !wine_config.exists() {
-        return None;
+    // Prefer custom_wine_path if it exists (this is the full path to wine executable)
+    if let Some(custom_path) = wine_section.get("custom_wine_path").filter(|v| !v.is_null()) {
+        let custom_path = custom_path
+            .as_str()
+            .ok_or_else(|| format!("{} has an unreadable wine.custom_wine_path", what))?;
+        // Strip /proton suffix to get the folder path (to match dropdown values)
+        let path = PathBuf::from(custom_path);
+        return Ok(Some(
+            path.parent()
+                .map(|parent| parent.to_string_lossy().to_string())
+                .unwrap_or_else(|| custom_path.to_string()),
+        ));
     }
 
-    let yaml_content = fs::read_to_string(&wine_config).ok()?;
-    let config: serde_yaml::Value = serde_yaml::from_str(&yaml_content).ok()?;
+    // If only version name is set (not custom_wine_path), Lutris looks it up in its runner directories
+    if let Some(version_name) = wine_section.get("version").filter(|v| !v.is_null()) {
+        let version_name = version_name
+            .as_str()
+            .ok_or_else(|| format!("{} has an unreadable wine.version", what))?;
+        let version_path = resolve_wine_version_name(version_name).ok_or_else(|| {
+            format!("{} uses wine version '{}', which was not found", what, version_name)
+        })?;
+        return Ok(Some(version_path.to_string_lossy().to_string()));
+    }
 
-    let wine_section = config.get("wine")?;
+    Ok(None)
+}
 
-    // Prefer custom_wine_path if it exists (this is the full path to wine executable)
-    if let Some(custom_path) = wine_section.get("custom_wine_path").and_then(|v| v.as_str()) {
-        println!("Lutris default wine (custom path): {}", custom_path);
-        // Strip /proton suffix to get the folder path (to match dropdown values)
-        let path = PathBuf::from(custom_path);
-        if let Some(parent) = path.parent() {
-            return Some(parent.to_string_lossy().to_string());
-        }
-        return Some(custom_path.to_string());
+/// Read a Lutris YAML config for a runner lookup, a missing file is an empty config
+fn read_runner_config(config_file: &std::path::Path, what: &str) -> Result<serde_yaml::Value, String> {
+    if !config_file.exists() {
+        return Ok(serde_yaml::Value::Null);
     }
 
-    // If only version name is set (not custom_wine_path), resolve it by looking in Lutris proton directory
-    if let Some(version_name) = wine_section.get("version").and_then(|v| v.as_str()) {
-        println!("Lutris default wine (version name): {}", version_name);
+    let yaml_content = fs::read_to_string(config_file)
+        .map_err(|e| format!("Failed to read {}: {}", what, e))?;
+    serde_yaml::from_str(&yaml_content).map_err(|e| format!("Failed to parse {}: {}", what, e))
+}
 
-        // Look for this version in Lutris proton directory
-        if let Some(proton_dir) = rustris_paths::lutris_proton_dir() {
-            let version_path = proton_dir.join(version_name);
-            if version_path.exists() {
-                println!("   Found version at: {}", version_path.display());
-                return Some(version_path.to_string_lossy().to_string());
-            } else {
-                println!("   Version '{}' not found in Lutris proton directory", version_name);
-            }
-        }
+/// Get the runner folder Lutris's default Wine version (runners/wine.yml) points at
+/// An unreadable wine.yml or a default that can't be found is an error, so callers
+/// deciding what to delete never mistake the default for an unused runner
+pub(crate) fn lutris_default_wine_runner() -> Result<Option<String>, String> {
+    let wine_config = rustris_paths::lutris_wine_config()
+        .ok_or("Could not get wine config path")?;
+
+    let config = read_runner_config(&wine_config, "runners/wine.yml")?;
+    wine_section_runner(&config, "runners/wine.yml")
+}
+
+/// Get the runner folder a game's config points at
+/// Errors when the config can't be read or its runner can't be found
+pub(crate) fn game_wine_runner(configpath: &str) -> Result<Option<String>, String> {
+    let config_file = rustris_paths::lutris_game_config(configpath)
+        .ok_or("Could not get game config path")?;
+    if !config_file.exists() {
+        return Err(format!("Config file does not exist: {:?}", config_file));
     }
 
-    None
+    let what = format!("games/{}.yml", configpath);
+    wine_section_runner(&read_runner_config(&config_file, &what)?, &what)
+}
+
+/// Get Lutris's default Wine version from runners/wine.yml
+/// Returns the full path to the wine version directory
+pub fn get_lutris_default_wine_version() -> Option<String> {
+    lutris_default_wine_runner().unwrap_or_else(|e| {
+        println!("Warning: {}", e);
+        None
+    })
 }
 
 /// Set Lutris's default Wine version in runners/wine.yml
@@ -517,61 +581,24 @@ pub fn set_lutris_default_wine_version(wine_path: &str) -> Result<(), String> {
     let wine_config = rustris_paths::lutris_wine_config()
         .ok_or("Could not get wine config path")?;
 
-    // Create parent directory if it doesn't exist
-    if let Some(parent) = wine_config.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create runners directory: {}", e))?;
-    }
-
-    // Load existing config or create new one
-    let mut config: serde_yaml::Value = if wine_config.exists() {
-        let yaml_content = fs::read_to_string(&wine_config)
-            .map_err(|e| format!("Failed to read wine.yml: {}", e))?;
-        serde_yaml::from_str(&yaml_content)
-            .unwrap_or(serde_yaml::Value::Mapping(serde_yaml::Mapping::new()))
-    } else {
-        serde_yaml::Value::Mapping(serde_yaml::Mapping::new())
-    };
-
-    // Get or create wine section
-    let wine_section = config
-        .as_mapping_mut()
-        .ok_or("Invalid YAML structure")?
-        .entry(serde_yaml::Value::String("wine".to_string()))
-        .or_insert(serde_yaml::Value::Mapping(serde_yaml::Mapping::new()));
-
     // Append /proton to the path (wine executable name)
     let wine_executable_path = PathBuf::from(wine_path).join("proton");
     let wine_executable_str = wine_executable_path.to_string_lossy().to_string();
 
     println!("   Wine executable path: {}", wine_executable_str);
 
-    // Set custom_wine_path
-    wine_section
-        .as_mapping_mut()
-        .ok_or("Wine section is not a mapping")?
-        .insert(
-            serde_yaml::Value::String("custom_wine_path".to_string()),
-            serde_yaml::Value::String(wine_executable_str),
-        );
-
-    // Write back to file
-    let updated_yaml = serde_yaml::to_string(&config)
-        .map_err(|e| format!("Failed to serialize YAML: {}", e))?;
-
-    fs::write(&wine_config, updated_yaml)
-        .map_err(|e| format!("Failed to write wine.yml: {}", e))?;
+    // Only custom_wine_path changes, every other runner option is kept as written
+    lutris_yaml::edit_file(&wine_config, |doc| {
+        doc.set_str(&["wine", "custom_wine_path"], &wine_executable_str)
+    })?;
 
     println!("   Lutris default wine version updated!");
 
     Ok(())
 }
 
-/// Update the Wine/Proton version for a specific game
-pub async fn update_game_wine_version(slug: &str, wine_version: &str) -> Result<(), String> {
-    println!("Updating wine version for game: {}", slug);
-    println!("   New version: {}", wine_version);
-
+/// Get the path of a game's Lutris config file (games/*.yml), which must exist
+pub(crate) fn game_config_file(slug: &str) -> Result<PathBuf, String> {
     // Get config path from Lutris database
     let db = LutrisDatabase::new()?;
     let configpath = db.get_configpath(slug)?;
@@ -584,48 +611,57 @@ pub async fn update_game_wine_version(slug: &str, wine_version: &str) -> Result<
         return Err(format!("Config file does not exist: {:?}", config_file));
     }
 
-    println!("   Config file: {:?}", config_file);
-
-    // Load existing config
-    let yaml_content = fs::read_to_string(&config_file)
-        .map_err(|e| format!("Failed to read config: {}", e))?;
-
-    let mut config: LutrisConfigFile = serde_yaml::from_str(&yaml_content)
-        .map_err(|e| format!("Failed to parse config: {}", e))?;
+    Ok(config_file)
+}
 
-    // Extract version name from path (e.g., "/path/to/rustris-GE-Proton10-27" -> "rustris-GE-Proton10-27")
-    let version_name = PathBuf::from(wine_version)
+/// Folder name Lutris stores as `wine.version`
+/// (e.g., "/path/to/rustris-GE-Proton10-27" -> "rustris-GE-Proton10-27")
+fn wine_version_name(wine_version: &str) -> Result<String, String> {
+    PathBuf::from(wine_version)
         .file_name()
         .and_then(|n| n.to_str())
-        .ok_or("Invalid wine version path")?
-        .to_string();
+        .map(|n| n.to_string())
+        .ok_or_else(|| "Invalid wine version path".to_string())
+}
 
-    println!("   Setting version to: {}", version_name);
+/// Update wine version using version field (not custom_wine_path)
+/// Lutris will find it in its runners directory
+fn set_wine_version(doc: &mut YamlDocument, version_name: &str) -> Result<(), String> {
+    doc.set_str(&["wine", "version"], version_name)?;
+    doc.remove(&["wine", "custom_wine_path"]).map(|_| ())
+}
 
-    // Update wine version using version field (not custom_wine_path)
-    // Lutris will find it in its runners directory
-    if let Some(wine_config) = &mut config.wine {
-        wine_config.version = Some(version_name.clone());
-        wine_config.custom_wine_path = None;  // Clear custom path
-    } else {
-        // Create wine section if it doesn't exist
-        config.wine = Some(WineConfig {
-            version: Some(version_name),
-            custom_wine_path: None,
-            battleye: None,
-            eac: None,
-            fsr: None,
-            overrides: None,
-            show_debug: None,
-        });
-    }
+/// Check that a game's wine version can be updated without writing anything
+/// Returns the config file and its current content, to put back if a later step fails
+pub(crate) fn check_game_wine_version_update(
+    slug: &str,
+    wine_version: &str,
+) -> Result<(PathBuf, String), String> {
+    let config_file = game_config_file(slug)?;
+    let version_name = wine_version_name(wine_version)?;
+
+    let text = fs::read_to_string(&config_file)
+        .map_err(|e| format!("Failed to read {:?}: {}", config_file, e))?;
+    let mut document = YamlDocument::parse(&text)
+        .map_err(|e| format!("Failed to parse {:?}: {}", config_file, e))?;
+    set_wine_version(&mut document, &version_name)?;
+
+    Ok((config_file, text))
+}
+
+/// Update the Wine/Proton version for a specific game
+pub async fn update_game_wine_version(slug: &str, wine_version: &str) -> Result<(), String> {
+    println!("Updating wine version for game: {}", slug);
+    println!("   New version: {}", wine_version);
+
+    let config_file = game_config_file(slug)?;
+    println!("   Config file: {:?}", config_file);
 
-    // Save back to file
-    let updated_yaml = serde_yaml::to_string(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let version_name = wine_version_name(wine_version)?;
+
+    println!("   Setting version to: {}", version_name);
 
-    fs::write(&config_file, updated_yaml)
-        .map_err(|e| format!("Failed to write config: {}", e))?;
+    lutris_yaml::edit_file(&config_file, |doc| set_wine_version(doc, &version_name))?;
 
     println!("   Wine version updated successfully!");
 
@@ -1,20 +1,44 @@
 /// Lutris domain commands - commands that interact with Lutris configuration
 use crate::lutris_cli;
 use crate::lutris_util::LutrisConfig;
+use crate::runner_metadata::{self, RunnerMetadata};
+use crate::runner_registry::{self, RunnerRecord};
+use crate::runner_sources::RunnerTarget;
+use crate::runner_usage;
+use crate::runner_version::{self, RunnerFamily};
 use crate::rustris_paths;
+use crate::steam_library;
 use std::fs;
+use std::path::Path;
 
 #[derive(Debug)]
 struct WineVersion {
     name: String,
     source: String,
     path: std::path::PathBuf,
+    metadata: RunnerMetadata,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct WineVersionInfo {
     pub path: String,
     pub display_name: String,
+    /// Naming scheme family, used to group the version dropdown
+    pub family: RunnerFamily,
+    /// Proton or Wine build, None when it could not be told
+    pub kind: Option<RunnerTarget>,
+    /// Where it was found: Lutris, Steam, Steam Flatpak or System
+    pub source: String,
+    /// Build time from the runner's `version` file, RFC3339
+    pub version_timestamp: Option<String>,
+    pub supports_win32: bool,
+    pub supports_win64: bool,
+    /// Disk usage, 0 unless listed with details
+    pub size_bytes: u64,
+    /// Number of installed games configured to use this runner, 0 unless listed with details
+    pub game_count: usize,
+    /// Install record if Rustris installed this runner
+    pub managed: Option<RunnerRecord>,
 }
 
 /// Get Lutris's global default wine version
@@ -39,8 +63,28 @@ pub async fn update_game_wine_version(slug: String, wine_version: String) -> Res
 }
 
 /// Get all available Wine/Proton versions from Lutris and Steam directories
+/// Includes Valve Proton builds from every Steam library
+/// Sizes and game counts are left at 0, this backs every version dropdown
 #[tauri::command]
-pub fn get_available_wine_versions() -> Result<Vec<WineVersionInfo>, String> {
+pub async fn get_available_wine_versions() -> Result<Vec<WineVersionInfo>, String> {
+    tokio::task::spawn_blocking(|| list_wine_versions(false))
+        .await
+        .map_err(|e| format!("Wine version scan failed: {}", e))?
+}
+
+/// Get all available Wine/Proton versions with their size and game count, for the runner manager
+/// Runs on a worker thread, sizing runners and reading every game config takes a while
+#[tauri::command]
+pub async fn get_wine_versions_with_details() -> Result<Vec<WineVersionInfo>, String> {
+    tokio::task::spawn_blocking(|| list_wine_versions(true))
+        .await
+        .map_err(|e| format!("Wine version scan failed: {}", e))?
+}
+
+/// Find the installed Wine/Proton versions
+/// `with_details` also measures each runner and counts the games using it, callers that
+/// only need paths and names should leave it off
+pub fn list_wine_versions(with_details: bool) -> Result<Vec<WineVersionInfo>, String> {
     let mut found_versions: Vec<WineVersion> = Vec::new();
 
     // Get wine/proton scan locations from centralized utility
@@ -50,53 +94,77 @@ pub fn get_available_wine_versions() -> Result<Vec<WineVersionInfo>, String> {
     for (location, source) in scan_locations {
         if let Ok(entries) = std::fs::read_dir(&location) {
             for entry in entries.flatten() {
+                // Skip hidden folders such as in-progress extraction staging directories
+                if entry.file_name().to_string_lossy().starts_with('.') {
+                    continue;
+                }
+
                 if entry.path().is_dir() {
-                    // Try to read version file for actual version name
-                    let version_file = entry.path().join("version");
-                    let version_name = if version_file.exists() {
-                        // Read version file and extract version name
-                        if let Ok(version_content) = fs::read_to_string(&version_file) {
-                            // Version file format: "timestamp VERSION_NAME"
-                            // e.g., "1762104463 GE-Proton10-25"
-                            version_content
-                                .split_whitespace()
-                                .nth(1)
-                                .map(|s| s.to_string())
-                                .unwrap_or_else(|| {
-                                    entry.file_name().to_string_lossy().to_string()
-                                })
-                        } else {
-                            entry.file_name().to_string_lossy().to_string()
-                        }
-                    } else {
-                        // Fall back to directory name
-                        entry.file_name().to_string_lossy().to_string()
-                    };
+                    // Prefer the version file, then compatibilitytool.vdf, then the directory name
+                    let metadata = runner_metadata::inspect_runner(&entry.path());
+                    let version_name = metadata
+                        .version_name
+                        .clone()
+                        .unwrap_or_else(|| entry.file_name().to_string_lossy().to_string());
 
                     found_versions.push(WineVersion {
                         name: version_name,
                         source: source.to_string(),
                         path: entry.path(),
+                        metadata,
                     });
                 }
             }
         }
     }
 
+    // Valve Proton builds (Proton 9.0, Experimental, Hotfix...) in every Steam library
+    for proton_dir in steam_library::valve_proton_dirs() {
+        let metadata = runner_metadata::inspect_runner(&proton_dir);
+
+        // Steam shows the folder name, the version file only has an internal build name
+        found_versions.push(WineVersion {
+            name: proton_dir.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            source: "Steam".to_string(),
+            path: proton_dir,
+            metadata,
+        });
+    }
+
     // Check for system wine
     let system_wine_paths = rustris_paths::system_wine_paths();
 
     for wine_path in system_wine_paths {
         if wine_path.exists() {
+            // /usr/bin/wine -> /usr holds the bin/ and lib/ trees
+            let prefix = wine_path.parent().and_then(|p| p.parent()).unwrap_or(Path::new("/usr"));
+            let (supports_win32, supports_win64) = runner_metadata::detect_architectures(prefix);
+
             found_versions.push(WineVersion {
                 name: "System Wine".to_string(),
                 source: "System".to_string(),
                 path: wine_path,
+                metadata: RunnerMetadata {
+                    kind: Some(RunnerTarget::Wine),
+                    supports_win32,
+                    supports_win64,
+                    ..Default::default()
+                },
             });
             break; // Only add System Wine once
         }
     }
 
+    // Provenance of runners Rustris installed itself
+    let records = runner_registry::list_records();
+
+    // Game configs are only needed for the usage count, a missing database just means no games
+    let game_references = if with_details {
+        runner_usage::game_runner_references().unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
     // Build final list with path as identifier and display name for UI
     let mut version_counts = std::collections::HashMap::new();
     for version in &found_versions {
@@ -113,15 +181,36 @@ pub fn get_available_wine_versions() -> Result<Vec<WineVersionInfo>, String> {
                 v.name.clone()
             };
 
+            let path = v.path.to_string_lossy().to_string();
+            let managed = records.iter().find(|r| r.path == path).cloned();
+            let game_count = game_references
+                .iter()
+                .filter(|(_, reference)| runner_usage::references_runner(reference, &path))
+                .count();
+            let size_bytes = if with_details && v.path.is_dir() {
+                runner_metadata::runner_size(&v.path)
+            } else {
+                0
+            };
+
             WineVersionInfo {
-                path: v.path.to_string_lossy().to_string(),
                 display_name,
+                family: runner_version::parse_runner_version(&v.name).family,
+                kind: v.metadata.kind,
+                source: v.source.clone(),
+                version_timestamp: v.metadata.version_timestamp.clone(),
+                supports_win32: v.metadata.supports_win32,
+                supports_win64: v.metadata.supports_win64,
+                size_bytes,
+                game_count,
+                managed,
+                path,
             }
         })
         .collect();
 
-    // Sort by display name
-    wine_versions.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+    // Group by family, newest first within each family
+    wine_versions.sort_by(|a, b| runner_version::compare_runner_names(&a.display_name, &b.display_name));
 
     // Remove exact duplicates (same path)
     wine_versions.dedup_by(|a, b| a.path == b.path);
@@ -88,17 +88,22 @@ impl LutrisDatabase {
             .map_err(|e| format!("Failed to connect to Lutris database: {}", e))
     }
 
-    /// Get the config path for a game by slug
-    pub fn get_configpath(&self, game_slug: &str) -> Result<String, String> {
+    /// Get a game by slug
+    pub fn get_game(&self, game_slug: &str) -> Result<LutrisDbGame, String> {
         use schema::games::dsl::*;
 
         let mut conn = self.connect()?;
 
-        let game: LutrisDbGame = games
+        games
             .filter(slug.eq(game_slug))
             .select(LutrisDbGame::as_select())
             .first(&mut conn)
-            .map_err(|e| format!("Game '{}' not found in database: {}", game_slug, e))?;
+            .map_err(|e| format!("Game '{}' not found in database: {}", game_slug, e))
+    }
+
+    /// Get the config path for a game by slug
+    pub fn get_configpath(&self, game_slug: &str) -> Result<String, String> {
+        let game = self.get_game(game_slug)?;
 
         game.configpath
             .ok_or_else(|| format!("Game '{}' has no config path in database", game_slug))
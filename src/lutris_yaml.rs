@@ -0,0 +1,919 @@
+/// Lossless editing of Lutris YAML configs (games/*.yml, runners/*.yml)
+/// Edits only rewrite the lines of the key they change, so unknown keys, key order and comments survive
+use serde_yaml::{Mapping, Value};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A YAML document edited line by line
+pub struct YamlDocument {
+    lines: Vec<String>,
+    trailing_newline: bool,
+}
+
+/// A parsed `key: value` line
+struct KeyLine {
+    key: String,
+    /// Inline value after the colon, without the trailing comment
+    value: String,
+    /// Trailing comment with the whitespace before its `#`
+    comment: Option<String>,
+}
+
+/// The key/value pairs of one mapping: lines `start..end`, keys at `indent`
+struct Block {
+    start: usize,
+    end: usize,
+    indent: usize,
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Whether a line holds YAML content rather than being blank or a comment
+fn is_content(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+fn is_sequence_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+/// Read a quoted scalar at the start of `text`, returns (value, bytes consumed)
+fn parse_quoted(text: &str) -> Option<(String, usize)> {
+    let quote = text.chars().next()?;
+    let mut value = String::new();
+    let mut chars = text.char_indices().skip(1).peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' if quote == '\'' => {
+                // '' is an escaped quote inside single quotes
+                if chars.peek().map(|(_, next)| *next) == Some('\'') {
+                    chars.next();
+                    value.push('\'');
+                } else {
+                    return Some((value, i + 1));
+                }
+            }
+            '"' if quote == '"' => return Some((value, i + 1)),
+            '\\' if quote == '"' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                other => value.push(other),
+            },
+            _ => value.push(c),
+        }
+    }
+
+    None
+}
+
+/// Split `value  # comment` into the value and the comment, ignoring `#` inside quotes
+fn split_comment(text: &str) -> (String, Option<String>) {
+    let mut quote: Option<char> = None;
+    let mut previous = ' ';
+
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some('"') if c == '\\' => {
+                previous = '\\';
+                continue;
+            }
+            Some(q) if c == q && !(q == '"' && previous == '\\') => quote = None,
+            Some(_) => {}
+            None if (c == '\'' || c == '"') && previous == ' ' => quote = Some(c),
+            None if c == '#' && previous.is_whitespace() => {
+                let value = text[..i].trim_end();
+                return (value.to_string(), Some(text[value.len()..].to_string()));
+            }
+            None => {}
+        }
+        previous = c;
+    }
+
+    (text.trim_end().to_string(), None)
+}
+
+fn parse_key_line(line: &str) -> Option<KeyLine> {
+    let text = line.trim_start_matches(' ');
+    if !is_content(text) || is_sequence_item(text) {
+        return None;
+    }
+
+    let (key, rest) = if text.starts_with('\'') || text.starts_with('"') {
+        let (key, consumed) = parse_quoted(text)?;
+        (key, text[consumed..].trim_start().strip_prefix(':')?)
+    } else {
+        // A plain key ends at the first colon followed by a space or the end of the line
+        let colon = text
+            .char_indices()
+            .find(|(i, c)| *c == ':' && (i + 1 == text.len() || text[i + 1..].starts_with(' ')))?
+            .0;
+        (text[..colon].trim_end().to_string(), &text[colon + 1..])
+    };
+
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+
+    let rest = rest.trim();
+    let (value, comment) = if rest.starts_with('#') {
+        (String::new(), Some(format!(" {}", rest)))
+    } else {
+        split_comment(rest)
+    };
+
+    Some(KeyLine {
+        key,
+        value,
+        comment,
+    })
+}
+
+/// YAML text of a scalar, quoted when needed
+fn render_scalar(value: &Value) -> Result<String, String> {
+    serde_yaml::to_string(value)
+        .map(|text| text.trim_end_matches('\n').to_string())
+        .map_err(|e| format!("Failed to serialize YAML value: {}", e))
+}
+
+/// Lines of a `key: value` entry at `indent`
+fn render_entry(key: &str, value: &Value, indent: usize) -> Result<Vec<String>, String> {
+    let pad = " ".repeat(indent);
+    let key = render_scalar(&Value::String(key.to_string()))?;
+
+    let is_block = match value {
+        Value::Mapping(m) => !m.is_empty(),
+        Value::Sequence(s) => !s.is_empty(),
+        _ => false,
+    };
+
+    let text = render_scalar(value)?;
+    let mut lines = text.lines();
+
+    if is_block {
+        // Nested mappings and sequences go on the following lines, one level deeper
+        let mut rendered = vec![format!("{}{}:", pad, key)];
+        rendered.extend(lines.map(|line| format!("{}  {}", pad, line)));
+        return Ok(rendered);
+    }
+
+    // Block scalars (|-) continue on the following lines, already indented by serde_yaml
+    let mut rendered = vec![format!("{}{}: {}", pad, key, lines.next().unwrap_or(""))];
+    rendered.extend(lines.map(|line| format!("{}{}", pad, line)));
+    Ok(rendered)
+}
+
+/// Set or remove a key in a parsed document, creating parent mappings like the line editor does
+fn apply_to_value(root: &mut Value, path: &[&str], value: Option<&Value>) {
+    let (last, parents) = match path.split_last() {
+        Some(split) => split,
+        None => return,
+    };
+
+    let mut current = root;
+    for key in parents {
+        if !current.is_mapping() {
+            *current = Value::Mapping(Mapping::new());
+        }
+        let map = current.as_mapping_mut().unwrap();
+        let key = Value::String(key.to_string());
+        if !map.get(&key).is_some_and(|v| v.is_mapping()) {
+            if value.is_none() {
+                return;
+            }
+            map.insert(key.clone(), Value::Mapping(Mapping::new()));
+        }
+        current = map.get_mut(&key).unwrap();
+    }
+
+    if !current.is_mapping() {
+        *current = Value::Mapping(Mapping::new());
+    }
+    let map = current.as_mapping_mut().unwrap();
+    match value {
+        Some(value) => {
+            map.insert(Value::String(last.to_string()), value.clone());
+        }
+        None => {
+            map.remove(last);
+        }
+    }
+}
+
+/// Rename a key in a parsed document, keeping its value
+fn rename_in_value(root: &mut Value, path: &[&str], new_key: &str) {
+    let (last, parents) = match path.split_last() {
+        Some(split) => split,
+        None => return,
+    };
+
+    let mut current = root;
+    for key in parents {
+        current = match current.get_mut(*key) {
+            Some(child) => child,
+            None => return,
+        };
+    }
+
+    if let Some(map) = current.as_mapping_mut() {
+        if let Some(value) = map.remove(*last) {
+            map.insert(Value::String(new_key.to_string()), value);
+        }
+    }
+}
+
+fn parse_value(text: &str) -> Result<Value, String> {
+    let value: Value =
+        serde_yaml::from_str(text).map_err(|e| format!("Config is not valid YAML: {}", e))?;
+
+    // An empty file is an empty mapping
+    Ok(match value {
+        Value::Null => Value::Mapping(Mapping::new()),
+        value => value,
+    })
+}
+
+impl YamlDocument {
+    /// Parse a document, refusing anything that is not a valid YAML mapping
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if !parse_value(text)?.is_mapping() {
+            return Err("Config is not a YAML mapping".to_string());
+        }
+
+        let mut lines: Vec<String> = text.lines().map(|l| l.to_string()).collect();
+        // A document holding only "{}" has no lines to keep
+        if lines
+            .iter()
+            .filter(|l| is_content(l))
+            .all(|l| l.trim() == "{}")
+        {
+            lines.retain(|l| !is_content(l));
+        }
+
+        Ok(Self {
+            lines,
+            trailing_newline: text.is_empty() || text.ends_with('\n'),
+        })
+    }
+
+    fn root(&self) -> Block {
+        let indent = self
+            .lines
+            .iter()
+            .find(|l| is_content(l))
+            .map(|l| indent_of(l))
+            .unwrap_or(0);
+
+        Block {
+            start: 0,
+            end: self.lines.len(),
+            indent,
+        }
+    }
+
+    /// One past the last line of the value of the key at `line`
+    /// Sequences may sit at the key's own indentation, as PyYAML writes them
+    fn extent_end(&self, line: usize, indent: usize) -> usize {
+        let mut end = line + 1;
+
+        for (i, text) in self.lines.iter().enumerate().skip(line + 1) {
+            if !is_content(text) {
+                continue;
+            }
+            let text_indent = indent_of(text);
+            if text_indent > indent || (text_indent == indent && is_sequence_item(&text[indent..]))
+            {
+                end = i + 1;
+            } else {
+                break;
+            }
+        }
+
+        end
+    }
+
+    fn find_child(&self, block: &Block, key: &str) -> Option<usize> {
+        (block.start..block.end).find(|&i| {
+            let line = &self.lines[i];
+            is_content(line)
+                && indent_of(line) == block.indent
+                && parse_key_line(line).is_some_and(|k| k.key == key)
+        })
+    }
+
+    /// The mapping under the key at `line`, turning an empty inline value (`{}`, `null`) into a block
+    fn child_block(&mut self, line: usize, indent: usize) -> Result<Block, String> {
+        let key_line = parse_key_line(&self.lines[line]).ok_or("Invalid config line")?;
+
+        if !key_line.value.is_empty() {
+            if !matches!(key_line.value.as_str(), "{}" | "null" | "~") {
+                return Err(format!("'{}' is not a mapping", key_line.key));
+            }
+            let mut rewritten = format!(
+                "{}{}:",
+                " ".repeat(indent),
+                render_scalar(&Value::String(key_line.key.clone()))?
+            );
+            if let Some(comment) = key_line.comment {
+                rewritten.push_str(&comment);
+            }
+            self.lines[line] = rewritten;
+        }
+
+        let end = self.extent_end(line, indent);
+        let child_indent = match (line + 1..end)
+            .map(|i| &self.lines[i])
+            .find(|l| is_content(l))
+        {
+            Some(first) if parse_key_line(first).is_some() => indent_of(first),
+            Some(_) => return Err(format!("'{}' is not a mapping", key_line.key)),
+            None => indent + 2,
+        };
+
+        Ok(Block {
+            start: line + 1,
+            end,
+            indent: child_indent,
+        })
+    }
+
+    /// Where a new key goes: after the last content line of the block
+    fn insert_point(&self, block: &Block) -> usize {
+        (block.start..block.end)
+            .rev()
+            .find(|&i| is_content(&self.lines[i]))
+            .map(|i| i + 1)
+            .unwrap_or(block.start)
+    }
+
+    /// The line of the key at `path` and the indentation of its mapping
+    /// None when a key is missing or a parent is not a block mapping
+    fn find_key_line(&mut self, path: &[&str]) -> Result<Option<(usize, usize)>, String> {
+        let mut block = self.root();
+
+        for (depth, key) in path.iter().enumerate() {
+            let line = match self.find_child(&block, key) {
+                Some(line) => line,
+                None => return Ok(None),
+            };
+
+            if depth == path.len() - 1 {
+                return Ok(Some((line, block.indent)));
+            }
+            if !parse_key_line(&self.lines[line]).is_some_and(|k| k.value.is_empty()) {
+                return Ok(None);
+            }
+            block = self.child_block(line, block.indent)?;
+        }
+
+        Ok(None)
+    }
+
+    /// Keys of a block with their lines `start..end`, each starting with the comments above the key
+    fn block_entries(&self, block: &Block) -> Vec<(String, usize, usize)> {
+        let mut entries = Vec::new();
+        let mut entry_start = block.start;
+        let mut i = block.start;
+
+        while i < block.end {
+            let line = &self.lines[i];
+            let key_line = if is_content(line) && indent_of(line) == block.indent {
+                parse_key_line(line)
+            } else {
+                None
+            };
+
+            match key_line {
+                Some(key_line) => {
+                    let end = self.extent_end(i, block.indent);
+                    entries.push((key_line.key, entry_start, end));
+                    entry_start = end;
+                    i = end;
+                }
+                None => i += 1,
+            }
+        }
+
+        entries
+    }
+
+    fn rename_line(&mut self, path: &[&str], new_key: &str) -> Result<bool, String> {
+        let (line, indent) = match self.find_key_line(path)? {
+            Some(found) => found,
+            None => return Err(format!("'{}' does not exist", path.join("."))),
+        };
+
+        let key_line = parse_key_line(&self.lines[line]).ok_or("Invalid config line")?;
+        let mut rewritten = format!(
+            "{}{}:",
+            " ".repeat(indent),
+            render_scalar(&Value::String(new_key.to_string()))?
+        );
+        if !key_line.value.is_empty() {
+            rewritten.push(' ');
+            rewritten.push_str(&key_line.value);
+        }
+        if let Some(comment) = key_line.comment {
+            rewritten.push_str(&comment);
+        }
+        self.lines[line] = rewritten;
+
+        Ok(true)
+    }
+
+    fn reorder_lines(&mut self, path: &[&str], keys: &[&str]) -> Result<bool, String> {
+        let (line, indent) = match self.find_key_line(path)? {
+            Some(found) => found,
+            None => return Err(format!("'{}' is not a mapping", path.join("."))),
+        };
+        if !parse_key_line(&self.lines[line]).is_some_and(|k| k.value.is_empty()) {
+            // An inline {} has nothing to reorder, other inline values are not edited
+            if keys.is_empty() {
+                return Ok(false);
+            }
+            return Err(format!("'{}' is not a block mapping", path.join(".")));
+        }
+
+        let block = self.child_block(line, indent)?;
+        let entries = self.block_entries(&block);
+
+        let mut reordered = Vec::with_capacity(block.end - block.start);
+        for key in keys {
+            let (_, start, end) = entries
+                .iter()
+                .find(|(k, _, _)| k == key)
+                .ok_or_else(|| format!("'{}.{}' does not exist", path.join("."), key))?;
+            reordered.extend_from_slice(&self.lines[*start..*end]);
+        }
+        let repeated = keys
+            .iter()
+            .enumerate()
+            .any(|(i, key)| keys[..i].contains(key));
+        if repeated || keys.len() != entries.len() {
+            return Err(format!(
+                "The new order of '{}' must list every key once",
+                path.join(".")
+            ));
+        }
+
+        self.lines.splice(block.start..block.end, reordered);
+        Ok(true)
+    }
+
+    fn set_lines(&mut self, path: &[&str], value: &Value) -> Result<(), String> {
+        let mut block = self.root();
+
+        for (depth, key) in path.iter().enumerate() {
+            let is_last = depth == path.len() - 1;
+
+            match self.find_child(&block, key) {
+                Some(line) if is_last => {
+                    let comment = parse_key_line(&self.lines[line]).and_then(|k| k.comment);
+                    let end = self.extent_end(line, block.indent);
+                    let mut entry = render_entry(key, value, block.indent)?;
+
+                    // Keep a trailing comment on single-line values
+                    if let (Some(comment), 1) = (comment, entry.len()) {
+                        entry[0].push_str(&comment);
+                    }
+                    self.lines.splice(line..end, entry);
+                    return Ok(());
+                }
+                Some(line) => block = self.child_block(line, block.indent)?,
+                None if is_last => {
+                    let at = self.insert_point(&block);
+                    let entry = render_entry(key, value, block.indent)?;
+                    self.lines.splice(at..at, entry);
+                    return Ok(());
+                }
+                None => {
+                    let at = self.insert_point(&block);
+                    let key_text = render_scalar(&Value::String(key.to_string()))?;
+                    self.lines
+                        .insert(at, format!("{}{}:", " ".repeat(block.indent), key_text));
+                    block = Block {
+                        start: at + 1,
+                        end: at + 1,
+                        indent: block.indent + 2,
+                    };
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn remove_lines(&mut self, path: &[&str]) -> Result<bool, String> {
+        let mut block = self.root();
+        let mut parent: Option<(usize, usize)> = None;
+
+        for (depth, key) in path.iter().enumerate() {
+            let line = match self.find_child(&block, key) {
+                Some(line) => line,
+                None => return Ok(false),
+            };
+
+            if depth < path.len() - 1 {
+                if !parse_key_line(&self.lines[line]).is_some_and(|k| k.value.is_empty()) {
+                    return Ok(false);
+                }
+                parent = Some((line, block.indent));
+                block = self.child_block(line, block.indent)?;
+                continue;
+            }
+
+            let end = self.extent_end(line, block.indent);
+            self.lines.drain(line..end);
+
+            // A mapping whose last key was removed is written as {} rather than null
+            if let Some((parent_line, parent_indent)) = parent {
+                let parent_end = self.extent_end(parent_line, parent_indent);
+                if !(parent_line + 1..parent_end).any(|i| is_content(&self.lines[i])) {
+                    let key_line =
+                        parse_key_line(&self.lines[parent_line]).ok_or("Invalid config line")?;
+                    let mut rewritten = format!(
+                        "{}{}: {{}}",
+                        " ".repeat(parent_indent),
+                        render_scalar(&Value::String(key_line.key))?
+                    );
+                    if let Some(comment) = key_line.comment {
+                        rewritten.push_str(&comment);
+                    }
+                    self.lines[parent_line] = rewritten;
+                }
+            }
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Apply a line edit to a copy and check the result means exactly the intended change
+    fn edit(
+        &mut self,
+        path: &[&str],
+        expect: impl FnOnce(&mut Value),
+        apply: impl FnOnce(&mut YamlDocument) -> Result<bool, String>,
+    ) -> Result<bool, String> {
+        if path.is_empty() {
+            return Err("Empty config key path".to_string());
+        }
+
+        let mut expected = parse_value(&self.to_string())?;
+        expect(&mut expected);
+
+        let mut edited = YamlDocument {
+            lines: self.lines.clone(),
+            trailing_newline: self.trailing_newline,
+        };
+        let changed = apply(&mut edited)?;
+
+        if parse_value(&edited.to_string())? != expected {
+            return Err(format!(
+                "Could not edit '{}' without changing other keys",
+                path.join(".")
+            ));
+        }
+
+        *self = edited;
+        Ok(changed)
+    }
+
+    /// The document as a YAML value
+    pub fn value(&self) -> Result<Value, String> {
+        parse_value(&self.to_string())
+    }
+
+    /// Set the value at a key path, creating missing parent mappings
+    pub fn set(&mut self, path: &[&str], value: Value) -> Result<(), String> {
+        self.edit(
+            path,
+            |expected| apply_to_value(expected, path, Some(&value)),
+            |doc| doc.set_lines(path, &value).map(|_| true),
+        )
+        .map(|_| ())
+    }
+
+    /// Set a string value at a key path
+    pub fn set_str(&mut self, path: &[&str], value: &str) -> Result<(), String> {
+        self.set(path, Value::String(value.to_string()))
+    }
+
+    /// Remove a key and everything under it, returns whether it existed
+    pub fn remove(&mut self, path: &[&str]) -> Result<bool, String> {
+        self.edit(
+            path,
+            |expected| apply_to_value(expected, path, None),
+            |doc| doc.remove_lines(path),
+        )
+    }
+
+    /// Rename a key, its value lines and comments stay as they are
+    pub fn rename(&mut self, path: &[&str], new_key: &str) -> Result<(), String> {
+        let (last, parents) = path.split_last().ok_or("Empty config key path")?;
+        if *last != new_key {
+            let mut sibling = parents.to_vec();
+            sibling.push(new_key);
+            if self.find_key_line(&sibling)?.is_some() {
+                return Err(format!("'{}' already exists", sibling.join(".")));
+            }
+        }
+
+        self.edit(
+            path,
+            |expected| rename_in_value(expected, path, new_key),
+            |doc| doc.rename_line(path, new_key),
+        )
+        .map(|_| ())
+    }
+
+    /// Put the keys of the mapping at `path` in the order of `keys`, which lists every key once
+    /// Each key moves with its value lines and the comments above it
+    pub fn reorder(&mut self, path: &[&str], keys: &[&str]) -> Result<(), String> {
+        self.edit(path, |_| {}, |doc| doc.reorder_lines(path, keys))
+            .map(|_| ())
+    }
+}
+
+impl fmt::Display for YamlDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines.join("\n"))?;
+        if self.trailing_newline && !self.lines.is_empty() {
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Read a YAML config file as a value, an empty mapping when it is missing or unreadable
+pub fn load_value(path: &Path) -> Value {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| parse_value(&text).ok())
+        .unwrap_or_else(|| Value::Mapping(Mapping::new()))
+}
+
+/// Edit a YAML config file in place, creating it if it does not exist
+/// The file is only replaced once every edit succeeded
+pub fn edit_file(
+    path: &Path,
+    edit: impl FnOnce(&mut YamlDocument) -> Result<(), String>,
+) -> Result<(), String> {
+    let text = if path.exists() {
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?
+    } else {
+        String::new()
+    };
+
+    let mut document =
+        YamlDocument::parse(&text).map_err(|e| format!("Failed to parse {:?}: {}", path, e))?;
+    edit(&mut document)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+
+    // Write to a temp file first so a crash never leaves a truncated config
+    let tmp_file = path.with_extension("yml.tmp");
+    fs::write(&tmp_file, document.to_string())
+        .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+    fs::rename(&tmp_file, path).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A GOG game config as Lutris writes it, with keys Rustris does not model
+    const WITCHER_CONFIG: &str = "\
+game:
+  args: -dx11 -skipintro
+  exe: /home/user/Games/witcher3/drive_c/GOG Games/The Witcher 3/bin/x64/witcher3.exe
+  prefix: /home/user/Games/witcher3
+  working_dir: /home/user/Games/witcher3/drive_c/GOG Games/The Witcher 3
+game_slug: the-witcher-3-wild-hunt
+name: The Witcher 3
+requires: null
+script:
+  files:
+  - setup: N/A:Select the setup file
+  installer:
+  - task:
+      arch: win64
+      name: create_prefix
+      prefix: $GAMEDIR
+slug: the-witcher-3-wild-hunt-gog
+system:
+  env:
+    DXVK_ASYNC: '1'
+    __GL_SHADER_DISK_CACHE: '1'
+  prefix_command: gamemoderun
+version: GOG
+wine:
+  dxvk: true
+  esync: true
+  version: lutris-GE-Proton8-26-x86_64
+year: 2015
+";
+
+    /// A Battle.net config edited by hand, with comments and a custom wine path
+    const BATTLENET_CONFIG: &str = "\
+# Overwatch via Battle.net
+game:
+  exe: drive_c/Program Files (x86)/Battle.net/Battle.net Launcher.exe
+  prefix: /home/user/Games/battlenet
+system:
+  disable_runtime: true
+wine:
+  # Needs a recent Proton for the launcher
+  custom_wine_path: /home/user/.local/share/lutris/runners/proton/GE-Proton9-20/proton  # pinned
+  overrides:
+    locationapi: disabled
+  fsr: false
+";
+
+    #[test]
+    fn test_set_existing_key_only_changes_its_line() {
+        let mut doc = YamlDocument::parse(WITCHER_CONFIG).unwrap();
+        doc.set_str(&["wine", "version"], "rustris-GE-Proton10-27")
+            .unwrap();
+
+        assert_eq!(
+            doc.to_string(),
+            WITCHER_CONFIG.replace(
+                "version: lutris-GE-Proton8-26-x86_64",
+                "version: rustris-GE-Proton10-27"
+            )
+        );
+    }
+
+    #[test]
+    fn test_switch_custom_path_to_version() {
+        let mut doc = YamlDocument::parse(BATTLENET_CONFIG).unwrap();
+        doc.set_str(&["wine", "version"], "rustris-GE-Proton10-27")
+            .unwrap();
+        assert!(doc.remove(&["wine", "custom_wine_path"]).unwrap());
+
+        assert_eq!(
+            doc.to_string(),
+            "\
+# Overwatch via Battle.net
+game:
+  exe: drive_c/Program Files (x86)/Battle.net/Battle.net Launcher.exe
+  prefix: /home/user/Games/battlenet
+system:
+  disable_runtime: true
+wine:
+  # Needs a recent Proton for the launcher
+  overrides:
+    locationapi: disabled
+  fsr: false
+  version: rustris-GE-Proton10-27
+"
+        );
+    }
+
+    #[test]
+    fn test_trailing_comment_and_quoting() {
+        let mut doc = YamlDocument::parse(BATTLENET_CONFIG).unwrap();
+        doc.set_str(&["wine", "custom_wine_path"], "/opt/wine: #1/bin/wine")
+            .unwrap();
+        doc.set_str(&["system", "env", "DXVK_HUD"], "1").unwrap();
+
+        let text = doc.to_string();
+        assert!(text.contains("  custom_wine_path: '/opt/wine: #1/bin/wine'  # pinned\n"));
+        assert!(text.ends_with("system:\n  disable_runtime: true\n  env:\n    DXVK_HUD: '1'\nwine:\n  # Needs a recent Proton for the launcher\n  custom_wine_path: '/opt/wine: #1/bin/wine'  # pinned\n  overrides:\n    locationapi: disabled\n  fsr: false\n"));
+
+        let value: Value = serde_yaml::from_str(&text).unwrap();
+        assert_eq!(
+            value["wine"]["custom_wine_path"].as_str(),
+            Some("/opt/wine: #1/bin/wine")
+        );
+        assert_eq!(value["system"]["env"]["DXVK_HUD"].as_str(), Some("1"));
+    }
+
+    #[test]
+    fn test_missing_and_empty_sections() {
+        let mut doc = YamlDocument::parse("game:\n  exe: game.exe\nwine: {}\n").unwrap();
+        doc.set_str(&["wine", "version"], "wine-ge-8-26").unwrap();
+        doc.set_str(&["system", "prefix_command"], "gamemoderun")
+            .unwrap();
+        assert_eq!(
+            doc.to_string(),
+            "game:\n  exe: game.exe\nwine:\n  version: wine-ge-8-26\nsystem:\n  prefix_command: gamemoderun\n"
+        );
+
+        let mut doc = YamlDocument::parse("").unwrap();
+        doc.set_str(&["wine", "custom_wine_path"], "/opt/proton/proton")
+            .unwrap();
+        assert_eq!(
+            doc.to_string(),
+            "wine:\n  custom_wine_path: /opt/proton/proton\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_last_key_leaves_empty_mapping() {
+        let mut doc = YamlDocument::parse(WITCHER_CONFIG).unwrap();
+        assert!(doc.remove(&["system", "env", "DXVK_ASYNC"]).unwrap());
+        assert!(doc
+            .remove(&["system", "env", "__GL_SHADER_DISK_CACHE"])
+            .unwrap());
+        assert!(!doc.remove(&["system", "env", "MISSING"]).unwrap());
+        assert!(!doc.remove(&["requires", "anything"]).unwrap());
+
+        assert!(doc
+            .to_string()
+            .contains("system:\n  env: {}\n  prefix_command: gamemoderun\n"));
+    }
+
+    #[test]
+    fn test_multiline_values_are_replaced_whole() {
+        let config = "\
+game:
+  args: >-
+    -windowed
+    -nosound
+  exe: game.exe
+";
+        let mut doc = YamlDocument::parse(config).unwrap();
+        doc.set_str(&["game", "args"], "-fullscreen").unwrap();
+        assert_eq!(
+            doc.to_string(),
+            "game:\n  args: -fullscreen\n  exe: game.exe\n"
+        );
+
+        doc.set_str(&["game", "args"], "line one\nline two")
+            .unwrap();
+        let value: Value = serde_yaml::from_str(&doc.to_string()).unwrap();
+        assert_eq!(value["game"]["args"].as_str(), Some("line one\nline two"));
+        assert_eq!(value["game"]["exe"].as_str(), Some("game.exe"));
+    }
+
+    #[test]
+    fn test_rename_and_reorder_keep_comments() {
+        let config = "\
+system:
+  env:
+    # Needed for the launcher
+    DXVK_ASYNC: 1
+    PULSE_LATENCY_MSEC: '60' # crackling audio
+    MANGOHUD_CONFIG: >-
+      fps,
+      frametime
+  prefix_command: gamemoderun
+";
+        let mut doc = YamlDocument::parse(config).unwrap();
+        doc.rename(&["system", "env", "PULSE_LATENCY_MSEC"], "PULSE_LATENCY")
+            .unwrap();
+        doc.reorder(
+            &["system", "env"],
+            &["MANGOHUD_CONFIG", "PULSE_LATENCY", "DXVK_ASYNC"],
+        )
+        .unwrap();
+
+        assert_eq!(
+            doc.to_string(),
+            "\
+system:
+  env:
+    MANGOHUD_CONFIG: >-
+      fps,
+      frametime
+    PULSE_LATENCY: '60' # crackling audio
+    # Needed for the launcher
+    DXVK_ASYNC: 1
+  prefix_command: gamemoderun
+"
+        );
+
+        assert!(doc
+            .rename(&["system", "env", "DXVK_ASYNC"], "PULSE_LATENCY")
+            .is_err());
+        assert!(doc.rename(&["system", "env", "MISSING"], "OTHER").is_err());
+        assert!(doc
+            .reorder(&["system", "env"], &["MANGOHUD_CONFIG", "DXVK_ASYNC"])
+            .is_err());
+        assert!(doc
+            .reorder(
+                &["system", "env"],
+                &["DXVK_ASYNC", "DXVK_ASYNC", "MANGOHUD_CONFIG"]
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_refuses_scalar_parents_and_invalid_yaml() {
+        let mut doc = YamlDocument::parse(WITCHER_CONFIG).unwrap();
+        assert!(doc.set_str(&["name", "nested"], "x").is_err());
+        assert!(doc.set_str(&["script", "files", "x"], "y").is_err());
+        assert_eq!(doc.to_string(), WITCHER_CONFIG);
+
+        assert!(YamlDocument::parse("game: [unterminated\n").is_err());
+        assert!(YamlDocument::parse("- not\n- a mapping\n").is_err());
+    }
+}
@@ -4,23 +4,59 @@
 // Logs should be plain text for parsing and readability in terminals.
 
 mod artwork_commands;
+mod dll_overrides;
+mod download_manager;
+mod effective_config;
+mod env_vars;
 mod game_commands;
 mod game_log_buffer;
+mod game_wine_options;
+mod github_api;
+mod http_client;
 mod installer_commands;
 mod lutris_api;
 mod lutris_cli;
 mod lutris_commands;
 mod lutris_db;
 mod lutris_util;
+mod lutris_yaml;
 mod proton_commands;
+mod release_notes;
+mod runner_archive;
+mod runner_dedupe;
+mod runner_metadata;
+mod runner_registry;
+mod runner_retention;
+mod runner_sources;
+mod runner_trash;
+mod runner_usage;
+mod runner_version;
 mod rustris_paths;
+mod rustris_settings;
+mod steam_library;
 mod utility_commands;
+mod vdf;
 
 use artwork_commands::save_artwork;
+use dll_overrides::{
+    apply_dll_preset, get_dll_overrides, list_dll_presets, remove_dll_override, set_dll_override,
+};
+use download_manager::{
+    cancel_download,
+    clear_finished_downloads,
+    list_downloads,
+    pause_download,
+    queue_runner_download,
+    resume_download,
+    set_download_concurrency,
+};
+use effective_config::get_effective_config;
+use env_vars::{add_env_var, get_env_vars, remove_env_var, reorder_env_vars, update_env_var};
 use game_commands::{
     check_game_running, clear_game_log, force_close_game, get_game_log,
     get_games, launch_game_by_slug, save_game_log, AppState,
 };
+use game_wine_options::{get_game_wine_options, set_game_wine_options};
 use tauri_plugin_global_shortcut::GlobalShortcutExt;
 use installer_commands::{run_wine_installer, run_lutris_installer_from_yaml};
 use lutris_api::{get_lutris_installer, get_lutris_installers, search_lutris_games};
@@ -28,14 +64,27 @@ use lutris_commands::{
     check_lutris_availability,
     get_available_wine_versions,
     get_lutris_global_default_wine_version,
+    get_wine_versions_with_details,
     set_lutris_global_default_wine_version,
     update_game_wine_version,
 };
 use proton_commands::{
     delete_proton_version,
     download_ge_proton,
+    download_runner,
     fetch_ge_proton_releases,
+    fetch_runner_releases,
+    get_runner_sources,
+    get_steam_compat_tools_dirs,
+    import_runner_archive,
 };
+use release_notes::get_runner_changelog;
+use runner_dedupe::{dedupe_runners, undedupe_runner};
+use runner_registry::get_runner_registry;
+use runner_retention::{apply_runner_retention, pin_runner, unpin_runner};
+use runner_trash::{empty_runner_trash, list_runner_trash, restore_runner_from_trash};
+use runner_usage::{get_runner_usage, prune_unused_runners};
+use rustris_settings::{get_rustris_settings, save_rustris_settings};
 use utility_commands::{
     check_for_crash_log,
     delete_crash_log,
@@ -146,6 +195,11 @@ fn main() {
                 })
                 .unwrap();
 
+            // Purge old trash once per start rather than on every trash listing
+            tauri::async_runtime::spawn_blocking(|| {
+                runner_trash::purge_expired(&rustris_settings::current_settings().trash);
+            });
+
             // Show the window after webview is ready to avoid white screen
             let window = app.get_webview_window("main").unwrap();
             tauri::async_runtime::spawn(async move {
@@ -176,11 +230,53 @@ fn main() {
             get_lutris_global_default_wine_version,
             set_lutris_global_default_wine_version,
             update_game_wine_version,
+            get_game_wine_options,
+            set_game_wine_options,
+            get_env_vars,
+            add_env_var,
+            update_env_var,
+            remove_env_var,
+            reorder_env_vars,
+            get_dll_overrides,
+            set_dll_override,
+            remove_dll_override,
+            list_dll_presets,
+            apply_dll_preset,
+            get_effective_config,
             get_available_wine_versions,
+            get_wine_versions_with_details,
             // Proton download and management
+            get_runner_sources,
+            get_steam_compat_tools_dirs,
+            fetch_runner_releases,
             fetch_ge_proton_releases,
+            get_runner_changelog,
+            download_runner,
             download_ge_proton,
+            import_runner_archive,
             delete_proton_version,
+            get_runner_registry,
+            get_runner_usage,
+            prune_unused_runners,
+            apply_runner_retention,
+            pin_runner,
+            unpin_runner,
+            list_runner_trash,
+            restore_runner_from_trash,
+            empty_runner_trash,
+            dedupe_runners,
+            undedupe_runner,
+            // Runner download queue
+            queue_runner_download,
+            list_downloads,
+            cancel_download,
+            pause_download,
+            resume_download,
+            clear_finished_downloads,
+            set_download_concurrency,
+            // Rustris settings
+            get_rustris_settings,
+            save_rustris_settings,
             // Lutris API
             save_artwork,
             search_lutris_games,
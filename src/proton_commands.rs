@@ -1,111 +1,215 @@
-/// GE-Proton download and management commands
+/// Runner download and management commands (GE-Proton, Wine-GE and other runner sources)
+use crate::download_manager::{
+    get_download_manager, wait_for_stop, DownloadControl, DownloadProgress, DownloadState,
+};
+use crate::http_client;
+use crate::release_notes;
+use crate::runner_archive::{self, CancelOnDrop};
+use crate::runner_registry::{self, RunnerRecord};
+use crate::runner_sources::{
+    self, ArchiveFormat, InstallDestination, ReleaseList, RunnerRelease, RunnerSource,
+    RunnerTarget,
+};
+use crate::runner_trash::{self, TrashEntry};
+use crate::runner_usage::{self, RunnerGame};
 use crate::rustris_paths;
 use std::fs;
-use tauri::Emitter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::watch;
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-pub struct GeProtonRelease {
-    pub tag_name: String,
-    pub name: String,
-    pub published_at: String,
-    pub download_url: String,
-    pub size_mb: f64,
+/// Numbers import staging directories, two imports of same-named archives must not share one
+static IMPORT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Runner source description for the frontend
+#[derive(Debug, serde::Serialize)]
+pub struct RunnerSourceInfo {
+    pub id: RunnerSource,
+    pub display_name: String,
+    pub install_target: RunnerTarget,
 }
 
-/// Fetch available GE-Proton releases from GitHub
+/// List all runner sources Rustris can download from
 #[tauri::command]
-pub async fn fetch_ge_proton_releases() -> Result<Vec<GeProtonRelease>, String> {
-    println!("📡 Fetching GE-Proton releases from GitHub...");
+pub fn get_runner_sources() -> Vec<RunnerSourceInfo> {
+    RunnerSource::all()
+        .iter()
+        .map(|source| RunnerSourceInfo {
+            id: *source,
+            display_name: source.display_name().to_string(),
+            install_target: source.install_target(),
+        })
+        .collect()
+}
 
-    let client = reqwest::Client::builder()
-        .user_agent("Rustris")
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+/// List the Steam compatibilitytools.d directories runners can also be installed into
+#[tauri::command]
+pub fn get_steam_compat_tools_dirs() -> Vec<String> {
+    rustris_paths::steam_compat_tools_dirs()
+        .iter()
+        .map(|(dir, _)| dir.to_string_lossy().to_string())
+        .collect()
+}
 
-    let response = client
-        .get("https://api.github.com/repos/GloriousEggroll/proton-ge-custom/releases")
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch releases: {}", e))?;
+/// Fetch available releases of a runner source
+/// When GitHub's rate limit is hit this returns the last cached list with `rate_limited` set
+#[tauri::command]
+pub async fn fetch_runner_releases(source: RunnerSource) -> Result<ReleaseList, String> {
+    println!("Fetching {} releases...", source.display_name());
 
-    let releases: Vec<serde_json::Value> = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
-
-    let mut ge_releases: Vec<GeProtonRelease> = Vec::new();
-
-    for release in releases.iter().take(30) {
-        let tag_name = release["tag_name"].as_str().unwrap_or("").to_string();
-        let name = release["name"].as_str().unwrap_or("").to_string();
-        let published_at = release["published_at"].as_str().unwrap_or("").to_string();
-
-        // Find the .tar.gz asset
-        if let Some(assets) = release["assets"].as_array() {
-            for asset in assets {
-                if let Some(asset_name) = asset["name"].as_str() {
-                    if asset_name.ends_with(".tar.gz") && !asset_name.ends_with(".sha512sum") {
-                        let download_url = asset["browser_download_url"]
-                            .as_str()
-                            .unwrap_or("")
-                            .to_string();
-                        let size_bytes = asset["size"].as_u64().unwrap_or(0);
-                        let size_mb = size_bytes as f64 / 1024.0 / 1024.0;
-
-                        ge_releases.push(GeProtonRelease {
-                            tag_name: tag_name.clone(),
-                            name: name.clone(),
-                            published_at,
-                            download_url,
-                            size_mb,
-                        });
-                        break;
-                    }
-                }
-            }
-        }
-    }
+    let list = runner_sources::fetch_releases(source).await?;
+    release_notes::store_release_notes(source, &list.releases);
 
-    println!("   Found {} GE-Proton releases", ge_releases.len());
-    for release in &ge_releases {
+    println!("   Found {} {} releases", list.releases.len(), source.display_name());
+    for release in &list.releases {
         println!("   - {} ({:.1} MB)", release.name, release.size_mb);
     }
 
-    Ok(ge_releases)
+    Ok(list)
+}
+
+/// Fetch available GE-Proton releases from GitHub
+#[tauri::command]
+pub async fn fetch_ge_proton_releases() -> Result<ReleaseList, String> {
+    fetch_runner_releases(RunnerSource::GeProton).await
 }
 
 /// Download and install a GE-Proton version
+/// `destinations` picks Lutris, Steam compatibilitytools.d directories or both, it defaults to Lutris
 #[tauri::command]
 pub async fn download_ge_proton(
     tag_name: String,
     download_url: String,
+    checksum_url: Option<String>,
+    destinations: Option<Vec<InstallDestination>>,
     app_handle: tauri::AppHandle,
 ) -> Result<String, String> {
-    println!("Downloading GE-Proton: {}", tag_name);
+    let release = RunnerRelease {
+        name: tag_name.clone(),
+        tag_name,
+        published_at: String::new(),
+        download_url,
+        size_mb: 0.0,
+        checksum_url,
+        body: String::new(),
+    };
+
+    download_runner(RunnerSource::GeProton, release, destinations, app_handle).await
+}
+
+/// Download and install a runner release from any source
+/// The download goes through the download queue, this resolves once it is installed
+#[tauri::command]
+pub async fn download_runner(
+    source: RunnerSource,
+    release: RunnerRelease,
+    destinations: Option<Vec<InstallDestination>>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let destinations = destinations.unwrap_or_else(|| vec![InstallDestination::Lutris]);
+    let (_, finished) = get_download_manager().enqueue(source, release, destinations, app_handle)?;
+
+    finished
+        .await
+        .map_err(|_| "Download was dropped from the queue".to_string())?
+}
+
+/// Where a release's archive is downloaded to
+/// Returns (completed archive path, partial download path)
+pub fn archive_paths(release: &RunnerRelease) -> Option<(PathBuf, PathBuf)> {
+    let downloads_dir = rustris_paths::rustris_downloads_dir()?;
+
+    // Name the archive after the URL so different releases never share a partial file
+    let archive_name = release
+        .download_url
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("{}.archive", release.tag_name));
+
+    Some((
+        downloads_dir.join(&archive_name),
+        downloads_dir.join(format!("{}.part", archive_name)),
+    ))
+}
+
+/// Where the ETag or Last-Modified of a partial download is kept, sent as If-Range on resume
+pub fn resume_validator_path(partial_path: &Path) -> PathBuf {
+    let mut path = partial_path.as_os_str().to_owned();
+    path.push(".validator");
+    PathBuf::from(path)
+}
+
+/// Download, verify and extract a runner release
+/// The archive is extracted into the first destination, the others get a hardlinked copy.
+/// Runs inside a download queue task, `control` tells it to pause or cancel
+pub async fn install_runner(
+    source: RunnerSource,
+    release: RunnerRelease,
+    destinations: Vec<InstallDestination>,
+    progress: &mut DownloadProgress,
+    mut control: watch::Receiver<DownloadControl>,
+) -> Result<String, String> {
+    // Refuses tags that could name a folder outside the destination
+    let prefixed_name = source.install_dir_name(&release.tag_name)?;
+
+    let (archive_path, partial_path) = archive_paths(&release)
+        .ok_or("Could not get Rustris downloads directory")?;
+
+    let tag_name = release.tag_name;
+    let download_url = release.download_url;
+    let checksum_url = release.checksum_url;
+
+    println!("Downloading {}: {}", source.display_name(), tag_name);
     println!("   URL: {}", download_url);
 
-    let proton_dir = rustris_paths::lutris_proton_dir()
-        .ok_or("Could not get Lutris proton directory")?;
+    // ~/.steam/root usually links to another Steam root, so compare where the directories resolve to
+    let mut target_dirs: Vec<PathBuf> = Vec::new();
+    let mut resolved_dirs: Vec<PathBuf> = Vec::new();
+    for destination in &destinations {
+        let dir = destination.dir(source)?;
+        let resolved = fs::canonicalize(&dir).unwrap_or_else(|_| dir.clone());
+        if !resolved_dirs.contains(&resolved) {
+            resolved_dirs.push(resolved);
+            target_dirs.push(dir);
+        }
+    }
+    let (target_dir, link_dirs) = target_dirs
+        .split_first()
+        .ok_or("No install destination given")?;
+    let target_dir = target_dir.clone();
 
-    // Create the proton directory if it doesn't exist
-    fs::create_dir_all(&proton_dir)
-        .map_err(|e| format!("Failed to create proton directory: {}", e))?;
+    // Create the runner directory if it doesn't exist
+    fs::create_dir_all(&target_dir)
+        .map_err(|e| format!("Failed to create runner directory: {}", e))?;
 
-    // Use rustris- prefix to distinguish from Lutris-managed versions
-    let prefixed_name = format!("rustris-{}", tag_name);
-    let installed_path = proton_dir.join(&prefixed_name);
+    // Other destinations share the runner's files, check they can before downloading anything
+    for link_dir in link_dirs {
+        fs::create_dir_all(link_dir)
+            .map_err(|e| format!("Failed to create runner directory: {}", e))?;
+        runner_archive::check_same_filesystem(&target_dir, link_dir)?;
+    }
 
-    // First check if it exists in Lutris directory
-    if installed_path.exists() {
-        return Err(format!(
-            "GE-Proton {} is already installed at {:?}",
-            tag_name,
-            installed_path
-        ));
+    // First check if it exists in any of the destinations
+    for dir in &target_dirs {
+        let installed_path = dir.join(&prefixed_name);
+        if installed_path.exists() {
+            return Err(format!(
+                "{} {} is already installed at {:?}",
+                source.display_name(),
+                tag_name,
+                installed_path
+            ));
+        }
     }
 
     // Check if this version exists in ANY wine directory (Lutris, Steam, etc.)
-    let all_versions = crate::lutris_commands::get_available_wine_versions()
+    let all_versions = tokio::task::spawn_blocking(|| crate::lutris_commands::list_wine_versions(false))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|versions| versions)
         .map_err(|e| format!("Failed to check existing versions: {}", e))?;
 
     for version in all_versions {
@@ -124,125 +228,849 @@ pub async fn download_ge_proton(
         }
     }
 
-    let client = reqwest::Client::builder()
-        .user_agent("Rustris")
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let client = http_client::download_client()?;
 
-    // Download the tar.gz file with progress tracking
-    println!("   📥 Downloading archive...");
-    let response = client
-        .get(&download_url)
-        .send()
+    if let Some(downloads_dir) = archive_path.parent() {
+        fs::create_dir_all(downloads_dir)
+            .map_err(|e| format!("Failed to create downloads directory: {}", e))?;
+    }
+
+    let archive_format = source.archive_format();
+
+    // A completed archive is left behind if a previous extraction failed or was paused, reuse it
+    if archive_path.exists() {
+        println!("   Reusing previously downloaded archive: {:?}", archive_path);
+    } else {
+        println!("   📥 Downloading archive to {:?}", partial_path);
+        download_with_resume(&client, &download_url, &partial_path, progress, &mut control).await?;
+
+        fs::rename(&partial_path, &archive_path)
+            .map_err(|e| format!("Failed to finalize downloaded archive: {}", e))?;
+        let _ = fs::remove_file(resume_validator_path(&partial_path));
+    }
+
+    // Verify the archive before anything is written to the runner directory
+    progress.set_state(DownloadState::Verifying);
+    let (checksum, verified) = match checksum_url {
+        Some(checksum_url) => {
+            println!("   Verifying archive checksum...");
+            match verify_archive_checksum(&client, &checksum_url, &archive_path).await {
+                Ok(checksum) => {
+                    println!("   Checksum OK");
+                    (checksum, true)
+                }
+                Err(e) => {
+                    // Drop the archive so the next attempt downloads a fresh copy
+                    let _ = fs::remove_file(&archive_path);
+                    return Err(e);
+                }
+            }
+        }
+        None if source.publishes_checksums() => {
+            return Err(format!(
+                "{} {} has no checksum asset, refusing to install an unverified archive",
+                source.display_name(),
+                tag_name
+            ));
+        }
+        None => {
+            println!(
+                "   Warning: {} publishes no checksums, {} is installed unverified",
+                source.display_name(),
+                tag_name
+            );
+            (hash_archive(&archive_path).await?, false)
+        }
+    };
+    let archive_size = fs::metadata(&archive_path).map(|m| m.len()).unwrap_or(0);
+
+    if *control.borrow() != DownloadControl::Run {
+        return Err("Download stopped before extraction".to_string());
+    }
+
+    println!("   Download complete, extracting...");
+    progress.set_state(DownloadState::Extracting);
+
+    // Extract into a staging directory and move the result into place
+    let cancel = Arc::new(AtomicBool::new(false));
+    let _cancel_on_drop = CancelOnDrop(cancel.clone());
+    let extract_archive = archive_path.clone();
+    let extract_target = target_dir.clone();
+    let install_name = prefixed_name.clone();
+    let extract_cancel = cancel.clone();
+    let mut extraction = tokio::task::spawn_blocking(move || {
+        runner_archive::extract_runner_archive(
+            &extract_archive,
+            archive_format,
+            &extract_target,
+            &install_name,
+            &extract_cancel,
+        )
+    });
+
+    // Pausing or cancelling stops the extraction, the archive is kept for a paused download
+    let extracted = tokio::select! {
+        result = &mut extraction => result,
+        _ = wait_for_stop(&mut control) => {
+            cancel.store(true, Ordering::SeqCst);
+            extraction.await
+        }
+    };
+    let installed_path = extracted.map_err(|e| format!("Extraction task failed: {}", e))??;
+    println!("   📁 Renamed to: {}", prefixed_name);
+
+    let record = RunnerRecord {
+        path: installed_path.to_string_lossy().to_string(),
+        name: prefixed_name.clone(),
+        source: Some(source),
+        release_tag: Some(tag_name.clone()),
+        imported_from: None,
+        installed_at: chrono::Local::now().to_rfc3339(),
+        checksum: Some(checksum),
+        verified,
+        archive_size,
+        disk_size: runner_registry::dir_size(&installed_path),
+    };
+    register_runner(record.clone());
+
+    // Every other destination gets its own copy sharing the same file data, tracked separately
+    for link_dir in link_dirs {
+        let link_source = installed_path.clone();
+        let link_target = link_dir.clone();
+        let link_name = prefixed_name.clone();
+        let link_cancel = cancel.clone();
+        let linked_path = tokio::task::spawn_blocking(move || {
+            runner_archive::link_runner(&link_source, &link_target, &link_name, &link_cancel)
+        })
+        .await
+        .map_err(|e| format!("Link task failed: {}", e))?
+        .map_err(|e| format!("Installed at {:?}, but adding it to {:?} failed: {}", installed_path, link_dir, e))?;
+
+        register_runner(RunnerRecord {
+            path: linked_path.to_string_lossy().to_string(),
+            ..record.clone()
+        });
+    }
+
+    // The archive is only kept around until it has been extracted successfully
+    if let Err(e) = fs::remove_file(&archive_path) {
+        println!("   Warning: failed to remove downloaded archive: {}", e);
+    }
+
+    println!(
+        "   {} {} installed successfully as {}!",
+        source.display_name(),
+        tag_name,
+        prefixed_name
+    );
+
+    // Return the path to the installed version
+    Ok(installed_path.to_string_lossy().to_string())
+}
+
+/// Import a runner from a local archive (.tar.gz, .tar.xz or .tar.zst)
+/// `target` picks runners/proton or runners/wine, it defaults to what the archive contains
+#[tauri::command]
+pub async fn import_runner_archive(
+    path: String,
+    target: Option<RunnerTarget>,
+) -> Result<String, String> {
+    println!("Importing runner archive: {}", path);
+
+    let archive_path = PathBuf::from(&path);
+    if !archive_path.is_file() {
+        return Err(format!("Archive not found: {}", path));
+    }
+
+    tokio::task::spawn_blocking(move || import_archive(&archive_path, target))
         .await
-        .map_err(|e| format!("Failed to download: {}", e))?;
+        .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+/// Extract, validate and install a local runner archive
+fn import_archive(archive_path: &Path, target: Option<RunnerTarget>) -> Result<String, String> {
+    let format = ArchiveFormat::detect(archive_path)?;
+    println!("   Detected {} archive", format.extension());
+
+    // Archive name without the .tar.* extension, used when there is no top-level folder
+    let file_name = archive_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let archive_stem = file_name
+        .split(".tar")
+        .next()
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or("runner")
+        .to_string();
+
+    // Stage inside the runners tree so the final rename stays on the same filesystem
+    let staging_dir = target
+        .unwrap_or(RunnerTarget::Proton)
+        .dir()
+        .ok_or("Could not get Lutris runner directory")?;
+    fs::create_dir_all(&staging_dir)
+        .map_err(|e| format!("Failed to create runner directory: {}", e))?;
+
+    // Downloads stage under their install name, which the queue keeps unique, imports need a suffix
+    let staging_name = format!(
+        "{}-import-{}-{}",
+        archive_stem,
+        std::process::id(),
+        IMPORT_COUNT.fetch_add(1, Ordering::SeqCst)
+    );
+
+    let cancel = AtomicBool::new(false);
+    let staged = runner_archive::stage_runner_archive(
+        archive_path,
+        format,
+        &staging_dir,
+        &staging_name,
+        &cancel,
+    )?;
+
+    let kind = runner_archive::detect_runner_kind(staged.root()).ok_or(
+        "Archive does not look like a Proton or Wine build (no proton script or bin/wine found)",
+    )?;
+    let target = target.unwrap_or(kind);
+    println!("   Detected {:?} build, installing into {:?} runners", kind, target);
+
+    let target_dir = target.dir().ok_or("Could not get Lutris runner directory")?;
+    fs::create_dir_all(&target_dir)
+        .map_err(|e| format!("Failed to create runner directory: {}", e))?;
+
+    // Use rustris- prefix to distinguish from Lutris-managed versions
+    let name = staged.root_name().unwrap_or(archive_stem);
+    let install_name = if name.starts_with("rustris-") {
+        name
+    } else {
+        format!("rustris-{}", name)
+    };
 
-    let total_size = response.content_length().unwrap_or(0);
+    let installed_path = staged.install(&target_dir, &install_name)?;
 
-    // Stream the download and track progress
+    register_runner(RunnerRecord {
+        path: installed_path.to_string_lossy().to_string(),
+        name: install_name,
+        source: None,
+        release_tag: None,
+        imported_from: Some(archive_path.to_string_lossy().to_string()),
+        installed_at: chrono::Local::now().to_rfc3339(),
+        checksum: sha512_file(archive_path).ok(),
+        verified: false,
+        archive_size: fs::metadata(archive_path).map(|m| m.len()).unwrap_or(0),
+        disk_size: runner_registry::dir_size(&installed_path),
+    });
+
+    println!("   Imported as {:?}", installed_path);
+    Ok(installed_path.to_string_lossy().to_string())
+}
+
+/// Add an installed runner to the registry
+/// The runner is already in place, so a registry failure is only logged
+fn register_runner(record: RunnerRecord) {
+    if let Err(e) = runner_registry::record_install(record) {
+        println!("   Warning: failed to update runner registry: {}", e);
+    }
+}
+
+/// Outcome of a single failed download attempt
+enum DownloadError {
+    /// Network hiccup - the partial file is kept and the download is resumed
+    Retryable(String),
+    /// The server refused the request - retrying will not help
+    Fatal(String),
+    /// The user paused or cancelled the download
+    Stopped(DownloadControl),
+}
+
+/// Stream a file to `partial_path`, resuming from whatever is already on disk
+/// Uses HTTP Range requests so a dropped connection or an app restart continues
+/// where the previous attempt stopped instead of starting over
+async fn download_with_resume(
+    client: &reqwest::Client,
+    url: &str,
+    partial_path: &Path,
+    progress: &mut DownloadProgress,
+    control: &mut watch::Receiver<DownloadControl>,
+) -> Result<(), String> {
+    let max_retries = http_client::max_retries();
+    let mut retry = 0;
+
+    loop {
+        match download_attempt(client, url, partial_path, progress, control).await {
+            Ok(()) => return Ok(()),
+            Err(DownloadError::Fatal(e)) => return Err(e),
+            Err(DownloadError::Stopped(DownloadControl::Pause)) => {
+                return Err("Download paused".to_string());
+            }
+            Err(DownloadError::Stopped(_)) => return Err("Download cancelled".to_string()),
+            Err(DownloadError::Retryable(e)) if retry < max_retries => {
+                retry += 1;
+                println!(
+                    "   Download interrupted ({}), resuming (retry {}/{})...",
+                    e, retry, max_retries
+                );
+                tokio::time::sleep(http_client::backoff_delay(retry)).await;
+            }
+            Err(DownloadError::Retryable(e)) => {
+                return Err(format!(
+                    "Download failed after {} attempts: {}",
+                    retry + 1, e
+                ));
+            }
+        }
+    }
+}
+
+/// Run one download attempt, appending to the partial file when the server honours the Range request
+async fn download_attempt(
+    client: &reqwest::Client,
+    url: &str,
+    partial_path: &Path,
+    progress: &mut DownloadProgress,
+    control: &mut watch::Receiver<DownloadControl>,
+) -> Result<(), DownloadError> {
     use futures_util::StreamExt;
-    let mut downloaded: u64 = 0;
+    use reqwest::StatusCode;
+    use tokio::io::AsyncWriteExt;
+
+    let validator_path = resume_validator_path(partial_path);
+    let validator = tokio::fs::read_to_string(&validator_path).await.ok();
+    let resume_from = tokio::fs::metadata(partial_path)
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+
+    // If-Range makes the server send the whole file instead when it has changed since
+    let mut request = client.get(url);
+    match validator {
+        Some(validator) if resume_from > 0 => {
+            println!("   Resuming download from byte {}", resume_from);
+            request = request
+                .header(reqwest::header::RANGE, format!("bytes={}-", resume_from))
+                .header(reqwest::header::IF_RANGE, validator.trim());
+        }
+        None if resume_from > 0 => {
+            println!("   Partial download has no validator to resume with, starting over");
+        }
+        _ => {}
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| DownloadError::Retryable(format!("Failed to download: {}", e)))?;
+
+    let append = match response.status() {
+        StatusCode::PARTIAL_CONTENT => true,
+        StatusCode::RANGE_NOT_SATISFIABLE => {
+            // Nothing left to fetch if the partial file already holds the whole archive
+            if content_range_total(&response) == Some(resume_from) {
+                return Ok(());
+            }
+
+            // The partial file does not match the remote archive, start over
+            let _ = tokio::fs::remove_file(partial_path).await;
+            return Err(DownloadError::Retryable(
+                "Partial download does not match the remote file".to_string(),
+            ));
+        }
+        // The server ignored the Range header and is sending the whole file
+        status if status.is_success() => false,
+        status => {
+            return Err(DownloadError::Fatal(format!(
+                "Download failed with HTTP status {}",
+                status
+            )));
+        }
+    };
+
+    // A fresh download, remember what it was so a later resume can't splice two versions
+    if !append {
+        let _ = match resume_validator(response.headers()) {
+            Some(validator) => tokio::fs::write(&validator_path, validator).await,
+            None => tokio::fs::remove_file(&validator_path).await,
+        };
+    }
+
+    let resumed_from = if append { resume_from } else { 0 };
+    let total_size = content_range_total(&response)
+        .or_else(|| response.content_length().map(|len| resumed_from + len))
+        .unwrap_or(0);
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(partial_path)
+        .await
+        .map_err(|e| DownloadError::Fatal(format!("Failed to open partial download: {}", e)))?;
+
+    // Stream the download to disk and track progress
+    let mut downloaded = resumed_from;
     let mut stream = response.bytes_stream();
-    let mut buffer = Vec::new();
 
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("Failed to read chunk: {}", e))?;
-        buffer.extend_from_slice(&chunk);
-        downloaded += chunk.len() as u64;
+    progress.start_attempt(resumed_from, total_size);
 
-        // Emit progress event
-        let progress = if total_size > 0 {
-            (downloaded as f64 / total_size as f64 * 100.0) as u32
-        } else {
-            0
+    loop {
+        let chunk = tokio::select! {
+            chunk = stream.next() => chunk,
+            stop = wait_for_stop(control) => {
+                // Keep what was received so far, a paused download resumes from here
+                let _ = file.flush().await;
+                return Err(DownloadError::Stopped(stop));
+            }
         };
 
-        let _ = app_handle.emit("download-progress", serde_json::json!({
-            "tag_name": tag_name,
-            "downloaded": downloaded,
-            "total": total_size,
-            "progress": progress,
-        }));
+        let chunk = match chunk {
+            Some(chunk) => chunk
+                .map_err(|e| DownloadError::Retryable(format!("Failed to read chunk: {}", e)))?,
+            None => break,
+        };
+
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| DownloadError::Fatal(format!("Failed to write to disk: {}", e)))?;
+        downloaded += chunk.len() as u64;
+
+        progress.report(downloaded, total_size);
     }
 
-    println!("   Download complete, extracting...");
+    file.flush()
+        .await
+        .map_err(|e| DownloadError::Fatal(format!("Failed to write to disk: {}", e)))?;
 
-    // Emit extraction status
-    let _ = app_handle.emit("download-progress", serde_json::json!({
-        "tag_name": tag_name,
-        "downloaded": total_size,
-        "total": total_size,
-        "progress": 100,
-        "extracting": true,
-    }));
-
-    // Extract the tar.gz directly to the proton directory
-    let decoder = flate2::read::GzDecoder::new(&buffer[..]);
-    let mut archive = tar::Archive::new(decoder);
-
-    archive
-        .unpack(&proton_dir)
-        .map_err(|e| format!("Failed to extract archive: {}", e))?;
-
-    // Rename the extracted folder to include rustris- prefix
-    let extracted_path = proton_dir.join(&tag_name);
-    if extracted_path.exists() {
-        fs::rename(&extracted_path, &installed_path)
-            .map_err(|e| format!("Failed to rename extracted folder: {}", e))?;
-        println!("   📁 Renamed to: {}", prefixed_name);
-    } else {
-        return Err(format!("Expected extracted folder not found: {:?}", extracted_path));
+    if total_size > 0 && downloaded < total_size {
+        return Err(DownloadError::Retryable(format!(
+            "Connection closed after {} of {} bytes",
+            downloaded, total_size
+        )));
+    }
+
+    Ok(())
+}
+
+/// Verify a downloaded archive against a published `.sha512sum` file
+/// The checksum file has the `sha512sum` format: "<hex digest>  <file name>"
+/// Returns the verified digest.
+async fn verify_archive_checksum(
+    client: &reqwest::Client,
+    checksum_url: &str,
+    archive_path: &Path,
+) -> Result<String, String> {
+    let response = http_client::send_with_retry(client.get(checksum_url))
+        .await
+        .map_err(|e| format!("Failed to download checksum: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to download checksum: HTTP status {}", response.status()));
     }
 
-    println!("   GE-Proton {} installed successfully as {}!", tag_name, prefixed_name);
+    let checksum_text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read checksum: {}", e))?;
 
-    // Return the path to the installed version
-    Ok(installed_path.to_string_lossy().to_string())
+    let archive_name = archive_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let expected = expected_checksum(&checksum_text, &archive_name)?;
+
+    let actual = hash_archive(archive_path).await?;
+
+    if actual != expected {
+        return Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}. The download may be corrupted or tampered with.",
+            archive_name,
+            expected,
+            actual
+        ));
+    }
+
+    Ok(actual)
+}
+
+/// Find the digest listed for `archive_name` in sha512sum output ("<hash>  <file name>" lines)
+fn expected_checksum(checksum_text: &str, archive_name: &str) -> Result<String, String> {
+    for line in checksum_text.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(hash), Some(listed)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+
+        // sha512sum marks binary mode with '*' and may list the file with a directory
+        let listed = listed.trim_start_matches('*');
+        let listed = listed.rsplit('/').next().unwrap_or(listed);
+
+        if listed == archive_name {
+            return Ok(hash.to_lowercase());
+        }
+    }
+
+    Err(format!("Checksum file lists no checksum for {}", archive_name))
+}
+
+/// Hash an archive off the async runtime
+async fn hash_archive(archive_path: &Path) -> Result<String, String> {
+    let hash_path = archive_path.to_path_buf();
+    tokio::task::spawn_blocking(move || sha512_file(&hash_path))
+        .await
+        .map_err(|e| format!("Checksum task failed: {}", e))?
+}
+
+/// Compute the lowercase hex SHA-512 digest of a file
+fn sha512_file(path: &Path) -> Result<String, String> {
+    use sha2::{Digest, Sha512};
+
+    let mut file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open archive for hashing: {}", e))?;
+    let mut hasher = Sha512::new();
+
+    std::io::copy(&mut file, &mut hasher)
+        .map_err(|e| format!("Failed to hash archive: {}", e))?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Read the full file size from a `Content-Range: bytes 0-99/1000` (or `bytes */1000`) header
+/// Strong ETag or else Last-Modified of a response, usable as If-Range
+/// Weak ETags are not allowed in If-Range
+fn resume_validator(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+
+    header(reqwest::header::ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(reqwest::header::LAST_MODIFIED))
+        .map(|v| v.to_string())
+}
+
+fn content_range_total(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .rsplit('/')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Outcome of deleting a runner
+#[derive(Debug, serde::Serialize)]
+pub struct RunnerDeleteReport {
+    pub path: String,
+    pub replacement: Option<String>,
+    /// Games that were moved to the replacement runner
+    pub migrated_games: Vec<RunnerGame>,
+    /// Trash entry the runner can be restored from
+    pub trash_id: String,
 }
 
 /// Delete a Proton version from wine/proton runners directories
+/// The runner is moved to the trash, games still using it are migrated to `replacement` first, deletion is refused without one
 #[tauri::command]
-pub fn delete_proton_version(path: String) -> Result<(), String> {
+pub async fn delete_proton_version(
+    path: String,
+    replacement: Option<String>,
+) -> Result<RunnerDeleteReport, String> {
     println!("Deleting proton version: {}", path);
 
-    let path_buf = std::path::PathBuf::from(&path);
+    // Run the safety checks before touching any game config
+    check_runner_deletable(&path)?;
+
+    let lookup_path = path.clone();
+    let dependents = tokio::task::spawn_blocking(move || runner_usage::games_using_runner(&lookup_path))
+        .await
+        .map_err(|e| format!("Runner usage task failed: {}", e))??;
+
+    let mut migrated_games = Vec::new();
+    let mut rollback = Vec::new();
+
+    if !dependents.is_empty() {
+        let names: Vec<&str> = dependents.iter().map(|g| g.name.as_str()).collect();
+        let replacement = replacement.as_deref().ok_or_else(|| {
+            format!(
+                "{} game(s) still use this runner: {}. Choose a replacement runner to move them to.",
+                dependents.len(),
+                names.join(", ")
+            )
+        })?;
+
+        if runner_usage::references_runner(replacement, &path) {
+            return Err("The replacement runner cannot be the runner being deleted".to_string());
+        }
+        if !Path::new(replacement).exists() {
+            return Err(format!("Replacement runner does not exist: {}", replacement));
+        }
+        // Game configs store only the folder name, which Lutris looks up in its own runner directories
+        if !runner_usage::is_in_lutris_runner_dir(replacement) {
+            return Err("The replacement runner must be installed in a Lutris runner directory".to_string());
+        }
+
+        // Check every config before changing any, keeping the originals to roll back to
+        let mut originals = Vec::new();
+        let mut failures = Vec::new();
+        for game in &dependents {
+            match crate::lutris_cli::check_game_wine_version_update(&game.slug, replacement) {
+                Ok(original) => originals.push(original),
+                Err(e) => failures.push(format!("{}: {}", game.name, e)),
+            }
+        }
+
+        if !failures.is_empty() {
+            return Err(format!(
+                "Cannot move {} game(s) to the replacement runner, nothing was changed. {}",
+                failures.len(),
+                failures.join("; ")
+            ));
+        }
+
+        println!("   Moving {} game(s) to {}", dependents.len(), replacement);
+
+        for (game, (config_file, original)) in dependents.into_iter().zip(originals) {
+            if let Err(e) = crate::lutris_cli::update_game_wine_version(&game.slug, replacement).await {
+                // Put the games already moved back on this runner so nothing is left half done
+                restore_game_configs(rollback);
+                return Err(format!(
+                    "Failed to move {} to the replacement runner, nothing was changed or deleted. {}",
+                    game.name, e
+                ));
+            }
+            rollback.push((config_file, original));
+            migrated_games.push(game);
+        }
+    }
+
+    let remove_path = path.clone();
+    let removed = tokio::task::spawn_blocking(move || remove_runner_dir(&remove_path))
+        .await
+        .map_err(|e| format!("Delete task failed: {}", e))
+        .and_then(|r| r);
+    let trash_entry = match removed {
+        Ok(entry) => entry,
+        Err(e) => {
+            restore_game_configs(rollback);
+            return Err(e);
+        }
+    };
+
+    Ok(RunnerDeleteReport {
+        path,
+        replacement,
+        migrated_games,
+        trash_id: trash_entry.id,
+    })
+}
+
+/// Write back game configs saved before a runner migration
+fn restore_game_configs(originals: Vec<(PathBuf, String)>) {
+    for (config_file, original) in originals {
+        if let Err(e) = fs::write(&config_file, original) {
+            println!("   Warning: failed to restore {:?}: {}", config_file, e);
+        }
+    }
+}
 
-    if !path_buf.exists() {
-        return Err("Proton version path does not exist".to_string());
+/// Decide whether a runner folder may be deleted
+/// The path must be a real directory (not a symlink) whose canonical parent is one of `roots`,
+/// roots that are symlinks themselves never count. Returns the canonical path to delete.
+pub fn check_runner_deletion_path(path: &Path, roots: &[PathBuf]) -> Result<PathBuf, String> {
+    let metadata = path
+        .symlink_metadata()
+        .map_err(|_| "Proton version path does not exist".to_string())?;
+
+    if metadata.file_type().is_symlink() {
+        return Err("Refusing to delete a symlink, remove the link target instead".to_string());
+    }
+    if !metadata.is_dir() {
+        return Err("Runner path is not a directory".to_string());
     }
 
-    // Safety check: only allow deletion from known wine/proton directories
-    let path_str = path_buf.to_string_lossy();
-    let allowed_paths = [
-        ".local/share/lutris/runners/wine",
-        ".local/share/lutris/runners/proton",
-    ];
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve runner path: {}", e))?;
+    let parent = canonical.parent().ok_or("Runner path has no parent directory")?;
+
+    let is_direct_child = roots.iter().any(|root| {
+        let root_is_symlink = root
+            .symlink_metadata()
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(true);
 
-    let is_allowed = allowed_paths.iter().any(|allowed| path_str.contains(allowed));
+        !root_is_symlink && root.canonicalize().map(|r| r == parent).unwrap_or(false)
+    });
 
-    if !is_allowed {
+    if !is_direct_child {
         return Err("Can only delete wine/proton versions from Rustris/Lutris directories".to_string());
     }
 
+    Ok(canonical)
+}
+
+/// Check that a runner may be deleted and get its canonical path
+/// Only direct children of the Lutris runner directories (and Steam ones for Rustris-installed
+/// runners) qualify, never the Lutris default
+fn check_runner_deletable(path: &str) -> Result<PathBuf, String> {
+    // Rustris-installed runners may also live in Steam compatibilitytools.d directories
+    let mut roots = rustris_paths::lutris_runner_roots();
+    if runner_registry::list_records().iter().any(|r| r.path == path) {
+        roots.extend(rustris_paths::steam_compat_tools_dirs().into_iter().map(|(dir, _)| dir));
+    }
+
+    let canonical = check_runner_deletion_path(Path::new(path), &roots)?;
+
     // Check if this is the default Lutris wine version
-    if let Some(default_wine) = crate::lutris_cli::get_lutris_default_wine_version() {
-        // The default wine version returns the folder path, possibly with /proton appended
-        // Compare both the direct path and the path with /proton removed
-        let default_path = std::path::PathBuf::from(&default_wine);
-        let default_path_str = default_path.to_string_lossy();
-
-        if path_str == default_path_str ||
-           path_str == default_path.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default() {
+    // The default is either the runner folder or an executable inside it
+    // An unreadable wine.yml could hide that this is the default, so it blocks the deletion
+    if let Some(default_wine) = crate::lutris_cli::lutris_default_wine_runner()? {
+        let default_path = PathBuf::from(&default_wine);
+        let default_path = default_path.canonicalize().unwrap_or(default_path);
+
+        if default_path.starts_with(&canonical) {
             return Err(
                 "Cannot delete the default Lutris Wine/Proton version. Please set a different default first.".to_string()
             );
         }
     }
 
-    // Delete the directory
-    fs::remove_dir_all(&path_buf)
-        .map_err(|e| format!("Failed to delete directory: {}", e))?;
+    Ok(canonical)
+}
+
+/// Move a runner folder to the trash and drop its registry record
+/// Callers must have made sure no game depends on it
+pub fn remove_runner_dir(path: &str) -> Result<TrashEntry, String> {
+    let canonical = check_runner_deletable(path)?;
+
+    // The record travels with the runner so a restore can put it back
+    let record = runner_registry::list_records().into_iter().find(|r| r.path == path);
+    let entry = runner_trash::move_to_trash(&canonical, record)?;
+
+    // The runner is already in the trash, a registry failure is only logged
+    match runner_registry::remove_record(path) {
+        Ok(Some(record)) => println!("   Removed {} from the runner registry", record.name),
+        Ok(None) => {}
+        Err(e) => println!("   Warning: failed to update runner registry: {}", e),
+    }
 
     println!("   Deleted successfully");
-    Ok(())
-}
\ No newline at end of file
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh directory under the system temp dir, unique per test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rustris-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_resume_validator() {
+        use reqwest::header::{HeaderMap, HeaderValue, ETAG, LAST_MODIFIED};
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(resume_validator(&headers), None);
+
+        headers.insert(LAST_MODIFIED, HeaderValue::from_static("Sat, 01 Feb 2025 10:00:00 GMT"));
+        headers.insert(ETAG, HeaderValue::from_static("W/\"weak\""));
+        assert_eq!(resume_validator(&headers).as_deref(), Some("Sat, 01 Feb 2025 10:00:00 GMT"));
+
+        headers.insert(ETAG, HeaderValue::from_static("\"0x8DD4\""));
+        assert_eq!(resume_validator(&headers).as_deref(), Some("\"0x8DD4\""));
+    }
+
+    #[test]
+    fn test_expected_checksum_matches_archive_name() {
+        let text = "AAA111  GE-Proton10-26.tar.gz\nbbb222 *build/GE-Proton10-27.tar.gz\n";
+
+        assert_eq!(expected_checksum(text, "GE-Proton10-27.tar.gz").unwrap(), "bbb222");
+        assert_eq!(expected_checksum(text, "GE-Proton10-26.tar.gz").unwrap(), "aaa111");
+    }
+
+    #[test]
+    fn test_expected_checksum_rejects_other_files() {
+        assert!(expected_checksum("aaa111  GE-Proton10-26.tar.gz\n", "GE-Proton10-27.tar.gz").is_err());
+        assert!(expected_checksum("aaa111\n", "GE-Proton10-27.tar.gz").is_err());
+        assert!(expected_checksum("", "GE-Proton10-27.tar.gz").is_err());
+    }
+
+    #[test]
+    fn test_allows_direct_child_of_root() {
+        let dir = test_dir("direct-child");
+        let root = dir.join("runners/proton");
+        fs::create_dir_all(root.join("GE-Proton10-27")).unwrap();
+        let roots = vec![root.clone()];
+
+        let result = check_runner_deletion_path(&root.join("GE-Proton10-27"), &roots);
+        assert_eq!(result.unwrap(), root.canonicalize().unwrap().join("GE-Proton10-27"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_traversal_out_of_root() {
+        let dir = test_dir("traversal");
+        let root = dir.join(".local/share/lutris/runners/wine");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(dir.join("important")).unwrap();
+        let roots = vec![root.clone()];
+
+        let sneaky = root.join("../../../../../important");
+        assert!(sneaky.exists());
+        assert!(check_runner_deletion_path(&sneaky, &roots).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_nested_and_root_paths() {
+        let dir = test_dir("nested");
+        let root = dir.join("runners/wine");
+        fs::create_dir_all(root.join("wine-ge/bin")).unwrap();
+        let roots = vec![root.clone()];
+
+        assert!(check_runner_deletion_path(&root.join("wine-ge/bin"), &roots).is_err());
+        assert!(check_runner_deletion_path(&root, &roots).is_err());
+        assert!(check_runner_deletion_path(&root.join("missing"), &roots).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_symlinked_runner() {
+        let dir = test_dir("symlinked-runner");
+        let root = dir.join("runners/proton");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(dir.join("elsewhere")).unwrap();
+        std::os::unix::fs::symlink(dir.join("elsewhere"), root.join("GE-Proton10-27")).unwrap();
+        let roots = vec![root.clone()];
+
+        assert!(check_runner_deletion_path(&root.join("GE-Proton10-27"), &roots).is_err());
+        assert!(dir.join("elsewhere").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_symlinked_root() {
+        let dir = test_dir("symlinked-root");
+        let real_root = dir.join("real-runners");
+        fs::create_dir_all(real_root.join("GE-Proton10-27")).unwrap();
+        let root = dir.join("runners");
+        std::os::unix::fs::symlink(&real_root, &root).unwrap();
+        let roots = vec![root.clone()];
+
+        assert!(check_runner_deletion_path(&root.join("GE-Proton10-27"), &roots).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,267 @@
+/// Runner release notes - caches the notes of fetched releases and builds changelogs between versions
+/// Stored as YAML in the Rustris data directory so changelogs work offline
+use crate::runner_registry;
+use crate::runner_sources::{self, RunnerRelease, RunnerSource};
+use crate::runner_usage::{self, RunnerGame};
+use crate::runner_version::{self, RunnerFamily, RunnerVersion};
+use crate::rustris_paths;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Serializes read-modify-write cycles of the cache file
+static NOTES_LOCK: Mutex<()> = Mutex::new(());
+
+/// Release notes of one runner release
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseNotes {
+    pub source: RunnerSource,
+    pub tag_name: String,
+    pub name: String,
+    pub published_at: String,
+    /// Markdown body of the release
+    pub body: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReleaseNotesCache {
+    #[serde(default)]
+    releases: Vec<ReleaseNotes>,
+}
+
+/// Changes between the runner a game uses and a newer release
+#[derive(Debug, Serialize)]
+pub struct RunnerChangelog {
+    pub game: RunnerGame,
+    /// Folder name of the runner the game uses now
+    pub current_runner: String,
+    pub candidate_tag: String,
+    /// Notes of every release after the current runner up to the candidate, newest first
+    pub releases: Vec<ReleaseNotes>,
+}
+
+fn load_cache() -> ReleaseNotesCache {
+    let cache_file = match rustris_paths::rustris_release_notes_cache() {
+        Some(f) if f.exists() => f,
+        _ => return ReleaseNotesCache::default(),
+    };
+
+    fs::read_to_string(&cache_file)
+        .ok()
+        .and_then(|content| serde_yaml::from_str(&content).ok())
+        .unwrap_or_else(|| {
+            println!(
+                "Warning: could not parse release notes cache {:?}",
+                cache_file
+            );
+            ReleaseNotesCache::default()
+        })
+}
+
+fn save_cache(cache: &ReleaseNotesCache) -> Result<(), String> {
+    let cache_file = rustris_paths::rustris_release_notes_cache()
+        .ok_or("Could not get release notes cache path")?;
+
+    if let Some(parent) = cache_file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create Rustris data directory: {}", e))?;
+    }
+
+    let yaml = serde_yaml::to_string(cache)
+        .map_err(|e| format!("Failed to serialize release notes: {}", e))?;
+
+    // Write to a temp file first so a crash never leaves a truncated cache
+    let tmp_file = cache_file.with_extension("yml.tmp");
+    fs::write(&tmp_file, yaml).map_err(|e| format!("Failed to write release notes: {}", e))?;
+    fs::rename(&tmp_file, &cache_file).map_err(|e| format!("Failed to write release notes: {}", e))
+}
+
+/// Cache the notes of freshly fetched releases, replacing older copies of the same release
+pub fn store_release_notes(source: RunnerSource, releases: &[RunnerRelease]) {
+    let _guard = NOTES_LOCK.lock().unwrap();
+    let mut cache = load_cache();
+
+    for release in releases.iter().filter(|r| !r.body.is_empty()) {
+        cache
+            .releases
+            .retain(|n| !(n.source == source && n.tag_name == release.tag_name));
+        cache.releases.push(ReleaseNotes {
+            source,
+            tag_name: release.tag_name.clone(),
+            name: release.name.clone(),
+            published_at: release.published_at.clone(),
+            body: release.body.clone(),
+        });
+    }
+
+    if let Err(e) = save_cache(&cache) {
+        println!("Warning: {}", e);
+    }
+}
+
+/// Get the cached notes of one source
+fn cached_notes(source: RunnerSource) -> Vec<ReleaseNotes> {
+    let _guard = NOTES_LOCK.lock().unwrap();
+
+    load_cache()
+        .releases
+        .into_iter()
+        .filter(|n| n.source == source)
+        .collect()
+}
+
+/// Pick the notes of the releases newer than `current` and no newer than `candidate`, newest first
+pub fn changelog_between(
+    notes: &[ReleaseNotes],
+    current: &RunnerVersion,
+    candidate: &RunnerVersion,
+) -> Vec<ReleaseNotes> {
+    let mut releases: Vec<ReleaseNotes> = notes
+        .iter()
+        .filter(|n| {
+            let version = runner_version::parse_runner_version(&n.tag_name);
+            version.cmp_newest_first(current) == Ordering::Less
+                && version.cmp_newest_first(candidate) != Ordering::Less
+        })
+        .cloned()
+        .collect();
+
+    releases.sort_by(|a, b| {
+        runner_version::parse_runner_version(&a.tag_name)
+            .cmp_newest_first(&runner_version::parse_runner_version(&b.tag_name))
+    });
+
+    releases
+}
+
+/// Family the installed builds of a source are recognised as
+fn source_family(source: RunnerSource) -> RunnerFamily {
+    match source {
+        RunnerSource::GeProton => RunnerFamily::GeProton,
+        RunnerSource::WineGe => RunnerFamily::WineGe,
+        RunnerSource::LutrisWine => RunnerFamily::LutrisWine,
+        RunnerSource::Kron4ekWine | RunnerSource::ProtonTkg => RunnerFamily::Other,
+    }
+}
+
+/// Work out which release of `source` a runner folder is
+/// Rustris-installed runners know their release tag, others are parsed from the folder name
+fn installed_version(source: RunnerSource, runner_path: &str) -> Result<RunnerVersion, String> {
+    let record = runner_registry::list_records()
+        .into_iter()
+        .find(|r| r.path == runner_path);
+
+    if let Some(record) = record {
+        if record.source == Some(source) {
+            if let Some(tag) = record.release_tag {
+                return Ok(runner_version::parse_runner_version(&tag));
+            }
+        }
+    }
+
+    let name = Path::new(runner_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| runner_path.to_string());
+    let version = runner_version::parse_runner_version(&name);
+
+    if version.family != source_family(source) {
+        return Err(format!("{} is not a {} build", name, source.display_name()));
+    }
+
+    Ok(version)
+}
+
+/// Get the combined changelog between the runner a game uses and a newer release of `source`
+#[tauri::command]
+pub async fn get_runner_changelog(
+    game_slug: String,
+    source: RunnerSource,
+    candidate_tag: String,
+) -> Result<RunnerChangelog, String> {
+    println!(
+        "Building {} changelog for {} up to {}",
+        source.display_name(),
+        game_slug,
+        candidate_tag
+    );
+
+    // Reads every game config and the runner registry, keep it off the async runtime
+    let lookup_slug = game_slug.clone();
+    let (game, current_path, current) = tokio::task::spawn_blocking(move || {
+        let (game, current_path) = runner_usage::game_runner_references()?
+            .into_iter()
+            .find(|(game, _)| game.slug == lookup_slug)
+            .ok_or_else(|| format!("{} does not use a Wine runner", lookup_slug))?;
+
+        let current = installed_version(source, &current_path)?;
+        Ok::<_, String>((game, current_path, current))
+    })
+    .await
+    .map_err(|e| format!("Runner lookup task failed: {}", e))??;
+    let candidate = runner_version::parse_runner_version(&candidate_tag);
+    if candidate.cmp_newest_first(&current) != Ordering::Less {
+        return Err(format!(
+            "{} is not newer than the runner {} uses",
+            candidate_tag, game.name
+        ));
+    }
+
+    let mut notes = cached_notes(source);
+    if !notes.iter().any(|n| n.tag_name == candidate_tag) {
+        // Notes for the candidate were never fetched, refresh the cache
+        let list = runner_sources::fetch_releases(source).await?;
+        store_release_notes(source, &list.releases);
+        notes = cached_notes(source);
+    }
+
+    let releases = changelog_between(&notes, &current, &candidate);
+    println!("   {} releases in changelog", releases.len());
+
+    Ok(RunnerChangelog {
+        game,
+        current_runner: Path::new(&current_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or(current_path),
+        candidate_tag,
+        releases,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes(tag_name: &str) -> ReleaseNotes {
+        ReleaseNotes {
+            source: RunnerSource::GeProton,
+            tag_name: tag_name.to_string(),
+            name: tag_name.to_string(),
+            published_at: String::new(),
+            body: format!("Changes in {}", tag_name),
+        }
+    }
+
+    #[test]
+    fn test_changelog_between() {
+        let cached = vec![
+            notes("GE-Proton10-27"),
+            notes("GE-Proton9-27"),
+            notes("GE-Proton10-25"),
+            notes("GE-Proton10-26"),
+            notes("GE-Proton10-3"),
+        ];
+
+        let current = runner_version::parse_runner_version("rustris-GE-Proton10-3");
+        let candidate = runner_version::parse_runner_version("GE-Proton10-26");
+        let tags: Vec<String> = changelog_between(&cached, &current, &candidate)
+            .into_iter()
+            .map(|n| n.tag_name)
+            .collect();
+
+        assert_eq!(tags, vec!["GE-Proton10-26", "GE-Proton10-25"]);
+    }
+}
@@ -0,0 +1,531 @@
+/// Runner archive extraction - unpacks into a staging directory, rejects unsafe entries
+/// and atomically moves the result into place
+use crate::runner_sources::{ArchiveFormat, RunnerTarget};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Staging directory that is removed when dropped, whether extraction succeeded, failed or was cancelled
+struct StagingDir {
+    path: PathBuf,
+}
+
+impl StagingDir {
+    fn create(path: PathBuf) -> Result<Self, String> {
+        // Leftover from a crashed or killed extraction
+        if path.exists() {
+            fs::remove_dir_all(&path)
+                .map_err(|e| format!("Failed to clear old staging directory: {}", e))?;
+        }
+
+        fs::create_dir_all(&path)
+            .map_err(|e| format!("Failed to create staging directory: {}", e))?;
+
+        Ok(Self { path })
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if self.path.exists() {
+            if let Err(e) = fs::remove_dir_all(&self.path) {
+                println!("   Warning: failed to clean up staging directory {:?}: {}", self.path, e);
+            }
+        }
+    }
+}
+
+/// Sets the cancel flag when dropped
+/// Hold one in an async task so dropping the task stops a blocking extraction
+pub struct CancelOnDrop(pub Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Check that an archive entry path stays inside the extraction directory
+/// Rejects absolute paths and any `..` component
+fn is_safe_entry_path(path: &Path) -> bool {
+    path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// An entry path without `.` components, as symlink entries are tracked
+fn normalize_entry_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect()
+}
+
+/// Whether a path goes through one of the archive's symlinks to reach something below it
+fn passes_through_symlink(path: &Path, symlinks: &HashSet<PathBuf>) -> bool {
+    normalize_entry_path(path).ancestors().skip(1).any(|a| symlinks.contains(a))
+}
+
+/// Check that a symlink entry's target stays inside the extraction directory
+/// Absolute targets are rejected, relative ones may not climb above the archive root
+/// or go through another symlink, which could point anywhere the lexical check can't see
+fn is_safe_link_target(entry_path: &Path, target: &Path, symlinks: &HashSet<PathBuf>) -> bool {
+    let mut resolved = PathBuf::new();
+    let parent = entry_path.parent().unwrap_or(Path::new(""));
+
+    for component in parent.components().chain(target.components()) {
+        // Pointing at another symlink is fine, continuing below it is not
+        if symlinks.contains(&resolved) {
+            return false;
+        }
+
+        match component {
+            Component::Normal(name) => resolved.push(name),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !resolved.pop() {
+                    return false;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+
+    true
+}
+
+/// An archive unpacked into its staging directory, waiting to be moved into place
+pub struct StagedRunner {
+    staging: StagingDir,
+    root: PathBuf,
+}
+
+impl StagedRunner {
+    /// The runner's top-level folder inside the staging directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Name of the archive's top-level folder, if it had a single one
+    pub fn root_name(&self) -> Option<String> {
+        if self.root == self.staging.path {
+            return None;
+        }
+        self.root.file_name().map(|n| n.to_string_lossy().to_string())
+    }
+
+    /// Atomically rename the runner to `target_dir/install_name`
+    /// The staging directory is cleaned up either way
+    pub fn install(self, target_dir: &Path, install_name: &str) -> Result<PathBuf, String> {
+        let installed_path = target_dir.join(install_name);
+        if installed_path.exists() {
+            return Err(format!("{:?} already exists", installed_path));
+        }
+
+        fs::rename(&self.root, &installed_path)
+            .map_err(|e| format!("Failed to move runner into place: {}", e))?;
+
+        Ok(installed_path)
+    }
+}
+
+/// Unpack a runner archive into a hidden staging directory inside `target_dir`
+///
+/// Staging next to the target keeps the final rename on the same filesystem.
+/// The archive's top-level folder is detected rather than assumed; archives without
+/// a single top-level folder use the staging directory itself as the runner root.
+pub fn stage_runner_archive(
+    archive_path: &Path,
+    format: ArchiveFormat,
+    target_dir: &Path,
+    staging_name: &str,
+    cancel: &AtomicBool,
+) -> Result<StagedRunner, String> {
+    let staging = StagingDir::create(target_dir.join(format!(".rustris-staging-{}", staging_name)))?;
+
+    let file = fs::File::open(archive_path)
+        .map_err(|e| format!("Failed to open archive: {}", e))?;
+    let decoder = format.decoder(std::io::BufReader::new(file))?;
+    let mut archive = tar::Archive::new(decoder);
+    archive.set_preserve_permissions(true);
+
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+
+    // Symlink targets are checked once every symlink in the archive is known,
+    // a link can be chained through one that comes later
+    let mut symlinks: HashSet<PathBuf> = HashSet::new();
+    let mut link_targets: Vec<(PathBuf, PathBuf)> = Vec::new();
+
+    for entry in entries {
+        if cancel.load(Ordering::SeqCst) {
+            return Err("Extraction cancelled".to_string());
+        }
+
+        let mut entry = entry.map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        let entry_path = entry
+            .path()
+            .map_err(|e| format!("Invalid path in archive: {}", e))?
+            .into_owned();
+
+        if !is_safe_entry_path(&entry_path) {
+            return Err(format!("Archive contains an unsafe path: {:?}", entry_path));
+        }
+
+        // Nothing may be written through a symlink unpacked earlier
+        if passes_through_symlink(&entry_path, &symlinks) {
+            return Err(format!("Archive entry is written through a symlink: {:?}", entry_path));
+        }
+
+        // Hard links must point inside the archive as well
+        if entry.header().entry_type().is_hard_link() {
+            if let Ok(Some(link)) = entry.link_name() {
+                if !is_safe_entry_path(&link) || passes_through_symlink(&link, &symlinks) {
+                    return Err(format!("Archive contains an unsafe hard link: {:?}", link));
+                }
+            }
+        }
+
+        // And so must symlinks, relative to where they are unpacked
+        if entry.header().entry_type().is_symlink() {
+            let link = entry
+                .link_name()
+                .map_err(|e| format!("Invalid symlink in archive: {}", e))?
+                .ok_or_else(|| format!("Symlink without a target in archive: {:?}", entry_path))?
+                .into_owned();
+            symlinks.insert(normalize_entry_path(&entry_path));
+            link_targets.push((entry_path.clone(), link));
+        }
+
+        let unpacked = entry
+            .unpack_in(&staging.path)
+            .map_err(|e| format!("Failed to extract {:?}: {}", entry_path, e))?;
+
+        if !unpacked {
+            return Err(format!("Archive entry escapes the extraction directory: {:?}", entry_path));
+        }
+    }
+
+    for (entry_path, link) in &link_targets {
+        if !is_safe_link_target(entry_path, link, &symlinks) {
+            return Err(format!(
+                "Archive contains a symlink pointing outside it: {:?} -> {:?}",
+                entry_path, link
+            ));
+        }
+    }
+
+    // Detect the real top-level folder instead of assuming its name
+    let top_level: Vec<PathBuf> = fs::read_dir(&staging.path)
+        .map_err(|e| format!("Failed to read staging directory: {}", e))?
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+
+    let root = match top_level.as_slice() {
+        [] => return Err("Archive is empty".to_string()),
+        [single] if single.is_dir() => single.clone(),
+        _ => staging.path.clone(),
+    };
+
+    println!("   Extracted root: {:?}", root.file_name().unwrap_or_default());
+
+    if cancel.load(Ordering::SeqCst) {
+        return Err("Extraction cancelled".to_string());
+    }
+
+    Ok(StagedRunner { staging, root })
+}
+
+/// Extract a runner archive and install it as `target_dir/install_name`
+/// Returns the installed path.
+pub fn extract_runner_archive(
+    archive_path: &Path,
+    format: ArchiveFormat,
+    target_dir: &Path,
+    install_name: &str,
+    cancel: &AtomicBool,
+) -> Result<PathBuf, String> {
+    if target_dir.join(install_name).exists() {
+        return Err(format!("{:?} already exists", target_dir.join(install_name)));
+    }
+
+    stage_runner_archive(archive_path, format, target_dir, install_name, cancel)?
+        .install(target_dir, install_name)
+}
+
+/// Work out whether an extracted folder is a Proton or a Wine build
+/// Proton builds ship a `proton` launcher script, Wine builds a `bin/wine` binary
+pub fn detect_runner_kind(root: &Path) -> Option<RunnerTarget> {
+    if root.join("proton").is_file() {
+        Some(RunnerTarget::Proton)
+    } else if root.join("bin/wine").is_file() || root.join("files/bin/wine").is_file() {
+        Some(RunnerTarget::Wine)
+    } else {
+        None
+    }
+}
+
+/// How the files of a linked runner copy were created
+#[derive(Debug, Default)]
+struct LinkStats {
+    hardlinked: u64,
+    reflinked: u64,
+}
+
+/// `FICLONE` ioctl from linux/fs.h: share the source file's extents (btrfs, XFS, bcachefs)
+const FICLONE: u64 = 0x40049409;
+
+/// Create `dst` as a copy-on-write clone of `src`
+fn reflink_file(src: &Path, dst: &Path) -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let src_file = fs::File::open(src)?;
+    let dst_file = fs::OpenOptions::new().write(true).create_new(true).open(dst)?;
+
+    // SAFETY: both descriptors are open for the duration of the call
+    let result = unsafe { libc::ioctl(dst_file.as_raw_fd(), FICLONE as _, src_file.as_raw_fd()) };
+    if result == -1 {
+        let error = std::io::Error::last_os_error();
+        drop(dst_file);
+        let _ = fs::remove_file(dst);
+        return Err(error);
+    }
+
+    dst_file.set_permissions(src_file.metadata()?.permissions())
+}
+
+/// Check that a runner in `installed_dir` can be linked into `target_dir`
+/// Hard links and reflinks both need the two directories on the same filesystem
+pub fn check_same_filesystem(installed_dir: &Path, target_dir: &Path) -> Result<(), String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let device = |dir: &Path| {
+        dir.metadata()
+            .map(|m| m.dev())
+            .map_err(|e| format!("Failed to read {:?}: {}", dir, e))
+    };
+
+    if device(installed_dir)? != device(target_dir)? {
+        return Err(format!(
+            "{:?} and {:?} are on different filesystems, install into them one at a time",
+            installed_dir, target_dir
+        ));
+    }
+
+    Ok(())
+}
+
+/// Recreate the tree under `src` at `dst`, sharing file data instead of copying it
+/// Tries a hard link first, then a reflink; nothing is ever copied
+fn link_tree(src: &Path, dst: &Path, stats: &mut LinkStats, cancel: &AtomicBool) -> Result<(), String> {
+    let entries = fs::read_dir(src).map_err(|e| format!("Failed to read {:?}: {}", src, e))?;
+
+    for entry in entries.flatten() {
+        if cancel.load(Ordering::SeqCst) {
+            return Err("Linking cancelled".to_string());
+        }
+
+        let from = entry.path();
+        let to = dst.join(entry.file_name());
+        let file_type = entry
+            .file_type()
+            .map_err(|e| format!("Failed to read {:?}: {}", from, e))?;
+
+        if file_type.is_symlink() {
+            let target = fs::read_link(&from)
+                .map_err(|e| format!("Failed to read link {:?}: {}", from, e))?;
+            std::os::unix::fs::symlink(&target, &to)
+                .map_err(|e| format!("Failed to create link {:?}: {}", to, e))?;
+        } else if file_type.is_dir() {
+            fs::create_dir(&to).map_err(|e| format!("Failed to create {:?}: {}", to, e))?;
+            if let Ok(metadata) = from.metadata() {
+                let _ = fs::set_permissions(&to, metadata.permissions());
+            }
+            link_tree(&from, &to, stats, cancel)?;
+        } else if fs::hard_link(&from, &to).is_ok() {
+            stats.hardlinked += 1;
+        } else {
+            reflink_file(&from, &to)
+                .map_err(|e| format!("Failed to hard link or reflink {:?}: {}", from, e))?;
+            stats.reflinked += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Install a second copy of an installed runner as `target_dir/install_name`
+/// Files are hardlinked or reflinked, the copy is built in a staging directory and renamed into place
+pub fn link_runner(
+    installed_path: &Path,
+    target_dir: &Path,
+    install_name: &str,
+    cancel: &AtomicBool,
+) -> Result<PathBuf, String> {
+    if target_dir.join(install_name).exists() {
+        return Err(format!("{:?} already exists", target_dir.join(install_name)));
+    }
+    check_same_filesystem(installed_path, target_dir)?;
+
+    let staging = StagingDir::create(target_dir.join(format!(".rustris-staging-{}", install_name)))?;
+    let root = staging.path.join(install_name);
+    fs::create_dir(&root).map_err(|e| format!("Failed to create staging directory: {}", e))?;
+
+    let mut stats = LinkStats::default();
+    link_tree(installed_path, &root, &mut stats, cancel)?;
+
+    println!(
+        "   Linked into {:?}: {} hardlinked, {} reflinked",
+        target_dir, stats.hardlinked, stats.reflinked
+    );
+
+    StagedRunner { staging, root }.install(target_dir, install_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh directory under the system temp dir, unique per test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("rustris-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Write a .tar.gz holding `runner/proton` plus a symlink `runner/lib/link` to `link_target`
+    fn write_archive(path: &Path, link_target: &str) {
+        let file = fs::File::create(path).unwrap();
+        let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::fast());
+        let mut builder = tar::Builder::new(encoder);
+
+        let script = b"#!/usr/bin/env python3\n";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(script.len() as u64);
+        header.set_mode(0o755);
+        builder
+            .append_data(&mut header, "runner/proton", &script[..])
+            .unwrap();
+
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder
+            .append_link(&mut header, "runner/lib/link", link_target)
+            .unwrap();
+
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_is_safe_entry_path() {
+        assert!(is_safe_entry_path(Path::new("GE-Proton10-27/files/bin/wine")));
+        assert!(is_safe_entry_path(Path::new("./GE-Proton10-27/proton")));
+        assert!(!is_safe_entry_path(Path::new("../evil")));
+        assert!(!is_safe_entry_path(Path::new("GE-Proton10-27/../../evil")));
+        assert!(!is_safe_entry_path(Path::new("/etc/passwd")));
+    }
+
+    #[test]
+    fn test_is_safe_link_target() {
+        let entry = Path::new("runner/files/lib/libwine.so");
+        let none = HashSet::new();
+        assert!(is_safe_link_target(entry, Path::new("libwine.so.1"), &none));
+        assert!(is_safe_link_target(entry, Path::new("../../proton"), &none));
+        assert!(!is_safe_link_target(entry, Path::new("../../../../outside"), &none));
+        assert!(!is_safe_link_target(entry, Path::new("/usr/lib/libwine.so"), &none));
+
+        let symlinks = HashSet::from([PathBuf::from("runner/files/lib/libwine.so.1")]);
+        assert!(is_safe_link_target(entry, Path::new("libwine.so.1"), &symlinks));
+        assert!(!is_safe_link_target(entry, Path::new("libwine.so.1/../x"), &symlinks));
+    }
+
+    /// Archive entry path and its symlink target, None for a small file
+    type TestEntry<'a> = (&'a str, Option<&'a str>);
+
+    /// Write a .tar.gz holding `entries` in order
+    fn write_entries(path: &Path, entries: &[TestEntry]) {
+        let file = fs::File::create(path).unwrap();
+        let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::fast());
+        let mut builder = tar::Builder::new(encoder);
+
+        for (entry_path, link_target) in entries {
+            let mut header = tar::Header::new_gnu();
+            match link_target {
+                Some(target) => {
+                    header.set_entry_type(tar::EntryType::Symlink);
+                    header.set_size(0);
+                    builder.append_link(&mut header, entry_path, target).unwrap();
+                }
+                None => {
+                    header.set_size(1);
+                    header.set_mode(0o644);
+                    builder.append_data(&mut header, entry_path, &b"x"[..]).unwrap();
+                }
+            }
+        }
+
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_rejects_chained_symlinks() {
+        let dir = test_dir("archive-chained");
+        let target_dir = dir.join("runners");
+        fs::create_dir_all(&target_dir).unwrap();
+        let cancel = AtomicBool::new(false);
+
+        let archives: [(&str, &[TestEntry]); 3] = [
+            // runner/x is the staging dir, so runner/x/y points above it
+            ("through-dir", &[("runner/x", Some("..")), ("runner/x/y", Some(".."))]),
+            // The link through runner/x comes before runner/x itself
+            ("out-of-order", &[("runner/y", Some("x/../..")), ("runner/x", Some(".."))]),
+            ("write-through", &[("runner/x", Some("..")), ("runner/x/evil", None)]),
+        ];
+
+        for (name, entries) in archives {
+            let archive = dir.join(format!("{}.tar.gz", name));
+            write_entries(&archive, entries);
+            let staged =
+                stage_runner_archive(&archive, ArchiveFormat::Gzip, &target_dir, name, &cancel);
+            assert!(staged.is_err(), "{}", name);
+        }
+        assert!(!dir.join("evil").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_stage_runner_archive() {
+        let dir = test_dir("archive-stage");
+        let cancel = AtomicBool::new(false);
+
+        let archive = dir.join("nested.tar.gz");
+        write_archive(&archive, "../proton");
+        let staged =
+            stage_runner_archive(&archive, ArchiveFormat::Gzip, &dir, "nested", &cancel).unwrap();
+        assert_eq!(staged.root_name().as_deref(), Some("runner"));
+        assert!(staged.root().join("proton").is_file());
+        assert_eq!(
+            fs::read_link(staged.root().join("lib/link")).unwrap(),
+            Path::new("../proton")
+        );
+        drop(staged);
+
+        for (name, target) in [("relative", "../../../outside"), ("absolute", "/etc/passwd")] {
+            let archive = dir.join(format!("{}.tar.gz", name));
+            write_archive(&archive, target);
+            assert!(
+                stage_runner_archive(&archive, ArchiveFormat::Gzip, &dir, name, &cancel).is_err(),
+                "{}",
+                target
+            );
+            assert!(!dir.join(format!(".rustris-staging-{}", name)).exists());
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,380 @@
+/// Runner deduplication - replaces identical files across installed runners with hardlinks
+/// Consecutive releases share most of their files, so this saves gigabytes per runner
+use crate::rustris_paths;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Serialize)]
+pub struct DedupeReport {
+    pub dry_run: bool,
+    pub runners_scanned: usize,
+    pub files_scanned: usize,
+    /// Files replaced (or that would be replaced) by a hardlink
+    pub files_linked: usize,
+    pub bytes_saved: u64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct UndedupeReport {
+    /// Hardlinked files that were given their own copy
+    pub files_copied: usize,
+    /// Extra space the runner now uses
+    pub bytes_copied: u64,
+}
+
+/// One inode found in the runners, with every path it was seen under
+struct Inode {
+    paths: Vec<PathBuf>,
+    nlink: u64,
+    size: u64,
+}
+
+/// Files can only be linked when they live on the same filesystem and have the same
+/// size and ownership/permissions, since hardlinks share all of those
+type GroupKey = (u64, u64, u32, u32, u32);
+
+/// Get every runner folder in the wine scan locations, skipping hidden folders and symlinks
+fn runner_dirs() -> Vec<PathBuf> {
+    let mut runners = Vec::new();
+
+    for (location, _) in rustris_paths::wine_scan_locations() {
+        if let Ok(entries) = fs::read_dir(&location) {
+            for entry in entries.flatten() {
+                let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+                let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                if is_dir && !is_hidden {
+                    runners.push(entry.path());
+                }
+            }
+        }
+    }
+
+    runners
+}
+
+/// Collect the regular files under a runner, symlinks are left alone
+fn collect_files(
+    dir: &Path,
+    inodes: &mut HashMap<(u64, u64), Inode>,
+    groups: &mut HashMap<GroupKey, Vec<(u64, u64)>>,
+    files_scanned: &mut usize,
+) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let metadata = match entry.path().symlink_metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        if metadata.is_dir() {
+            collect_files(&entry.path(), inodes, groups, files_scanned);
+            continue;
+        }
+        if !metadata.is_file() || metadata.len() == 0 {
+            continue;
+        }
+
+        *files_scanned += 1;
+        let inode_key = (metadata.dev(), metadata.ino());
+
+        let inode = inodes.entry(inode_key).or_insert_with(|| {
+            let group_key = (
+                metadata.dev(),
+                metadata.len(),
+                metadata.mode(),
+                metadata.uid(),
+                metadata.gid(),
+            );
+            groups.entry(group_key).or_default().push(inode_key);
+
+            Inode {
+                paths: Vec::new(),
+                nlink: metadata.nlink(),
+                size: metadata.len(),
+            }
+        });
+        inode.paths.push(entry.path());
+    }
+}
+
+/// Hex SHA-256 of a file
+fn hash_file(path: &Path) -> Option<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut file = fs::File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).ok()?;
+
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// Atomically replace `target` with a hardlink to `keep`
+fn replace_with_link(keep: &Path, target: &Path) -> Result<(), String> {
+    let file_name = target
+        .file_name()
+        .ok_or("Invalid file path")?
+        .to_string_lossy();
+    let tmp = target.with_file_name(format!(".rustris-dedupe-{}", file_name));
+
+    fs::hard_link(keep, &tmp).map_err(|e| format!("Failed to link {:?}: {}", target, e))?;
+    fs::rename(&tmp, target).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("Failed to replace {:?}: {}", target, e)
+    })
+}
+
+/// Hardlink identical files across every installed runner
+pub fn dedupe(dry_run: bool) -> Result<DedupeReport, String> {
+    println!(
+        "Deduplicating runners{}",
+        if dry_run { " (dry run)" } else { "" }
+    );
+
+    dedupe_runner_dirs(&runner_dirs(), dry_run)
+}
+
+/// Hardlink identical files across `runners`
+fn dedupe_runner_dirs(runners: &[PathBuf], dry_run: bool) -> Result<DedupeReport, String> {
+    let mut report = DedupeReport {
+        dry_run,
+        runners_scanned: runners.len(),
+        ..Default::default()
+    };
+
+    let mut inodes: HashMap<(u64, u64), Inode> = HashMap::new();
+    let mut groups: HashMap<GroupKey, Vec<(u64, u64)>> = HashMap::new();
+    for runner in runners {
+        collect_files(runner, &mut inodes, &mut groups, &mut report.files_scanned);
+    }
+
+    for candidates in groups.values().filter(|c| c.len() > 1) {
+        // Only files whose size and permissions already match get hashed
+        let mut by_hash: HashMap<String, Vec<(u64, u64)>> = HashMap::new();
+        for inode_key in candidates {
+            if let Some(hash) = inodes.get(inode_key).and_then(|i| hash_file(&i.paths[0])) {
+                by_hash.entry(hash).or_default().push(*inode_key);
+            }
+        }
+
+        for same_content in by_hash.values().filter(|s| s.len() > 1) {
+            let keep = inodes[&same_content[0]].paths[0].clone();
+
+            for inode_key in &same_content[1..] {
+                let inode = &inodes[inode_key];
+                let mut linked = 0;
+
+                for path in &inode.paths {
+                    if dry_run || replace_with_link(&keep, path).is_ok() {
+                        linked += 1;
+                    } else {
+                        println!("   Warning: could not link {:?}", path);
+                    }
+                }
+
+                report.files_linked += linked;
+
+                // The data is only freed once no other name (inside or outside the runners) refers to it
+                if linked == inode.paths.len() && inode.nlink as usize == linked {
+                    report.bytes_saved += inode.size;
+                }
+            }
+        }
+    }
+
+    println!(
+        "   {} files in {} runners, {} linked, {} bytes saved",
+        report.files_scanned, report.runners_scanned, report.files_linked, report.bytes_saved
+    );
+    Ok(report)
+}
+
+/// Give every file a runner shares with other runners its own copy again
+/// Use before modifying a runner in place, so the change does not leak into other runners
+pub fn undedupe(runner_path: &Path) -> Result<UndedupeReport, String> {
+    if !runner_dirs().iter().any(|r| r == runner_path) {
+        return Err("Not an installed runner".to_string());
+    }
+
+    undedupe_dir(runner_path)
+}
+
+/// Copy the files of `runner_path` that are hardlinked from outside it
+/// Links between files inside the runner are kept, the copy takes their place
+fn undedupe_dir(runner_path: &Path) -> Result<UndedupeReport, String> {
+    println!("Un-deduplicating {:?}", runner_path);
+
+    let mut inodes: HashMap<(u64, u64), Inode> = HashMap::new();
+    collect_linked_files(runner_path, &mut inodes)?;
+
+    let mut report = UndedupeReport::default();
+    for inode in inodes.values() {
+        // Every name of the inode is inside the runner, nothing is shared
+        if inode.paths.len() as u64 >= inode.nlink {
+            continue;
+        }
+
+        let (first, others) = match inode.paths.split_first() {
+            Some(paths) => paths,
+            None => continue,
+        };
+        copy_in_place(first)?;
+        for other in others {
+            replace_with_link(first, other)?;
+        }
+
+        report.files_copied += 1;
+        report.bytes_copied += inode.size;
+    }
+
+    println!(
+        "   {} files copied ({} bytes)",
+        report.files_copied, report.bytes_copied
+    );
+    Ok(report)
+}
+
+/// Collect the regular files under `dir` that have more than one name
+fn collect_linked_files(dir: &Path, inodes: &mut HashMap<(u64, u64), Inode>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let metadata = match path.symlink_metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        if metadata.is_dir() {
+            collect_linked_files(&path, inodes)?;
+            continue;
+        }
+        if !metadata.is_file() || metadata.nlink() < 2 {
+            continue;
+        }
+
+        inodes
+            .entry((metadata.dev(), metadata.ino()))
+            .or_insert_with(|| Inode {
+                paths: Vec::new(),
+                nlink: metadata.nlink(),
+                size: metadata.len(),
+            })
+            .paths
+            .push(path);
+    }
+
+    Ok(())
+}
+
+/// Replace a file with a copy of itself, its other names keep the old inode
+fn copy_in_place(path: &Path) -> Result<(), String> {
+    let file_name = path
+        .file_name()
+        .ok_or("Invalid file path")?
+        .to_string_lossy();
+    let tmp = path.with_file_name(format!(".rustris-undedupe-{}", file_name));
+
+    fs::copy(path, &tmp).map_err(|e| format!("Failed to copy {:?}: {}", path, e))?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("Failed to replace {:?}: {}", path, e)
+    })
+}
+
+/// Hardlink identical files across all installed runners
+#[tauri::command]
+pub async fn dedupe_runners(dry_run: bool) -> Result<DedupeReport, String> {
+    tokio::task::spawn_blocking(move || dedupe(dry_run))
+        .await
+        .map_err(|e| format!("Dedupe task failed: {}", e))?
+}
+
+/// Give a runner its own copy of every file it shares with other runners
+#[tauri::command]
+pub async fn undedupe_runner(path: String) -> Result<UndedupeReport, String> {
+    tokio::task::spawn_blocking(move || undedupe(Path::new(&path)))
+        .await
+        .map_err(|e| format!("Un-dedupe task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh directory under the system temp dir, unique per test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("rustris-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn ino(path: &Path) -> u64 {
+        path.metadata().unwrap().ino()
+    }
+
+    #[test]
+    fn test_hash_file() {
+        let dir = test_dir("dedupe-hash");
+        fs::write(dir.join("a"), "same").unwrap();
+        fs::write(dir.join("b"), "same").unwrap();
+        fs::write(dir.join("c"), "other").unwrap();
+
+        assert_eq!(hash_file(&dir.join("a")), hash_file(&dir.join("b")));
+        assert_ne!(hash_file(&dir.join("a")), hash_file(&dir.join("c")));
+        assert_eq!(hash_file(&dir.join("missing")), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dedupe_and_undedupe() {
+        let dir = test_dir("dedupe-link");
+        let (old, new) = (dir.join("GE-Proton10-26"), dir.join("GE-Proton10-27"));
+        for runner in [&old, &new] {
+            fs::create_dir_all(runner.join("files/lib")).unwrap();
+            fs::write(runner.join("files/lib/wine.dll"), "shared library").unwrap();
+        }
+        fs::write(old.join("proton"), "old script").unwrap();
+        fs::write(new.join("proton"), "new script").unwrap();
+
+        let report = dedupe_runner_dirs(&[old.clone(), new.clone()], false).unwrap();
+        assert_eq!(report.files_linked, 1);
+        assert_eq!(report.bytes_saved, "shared library".len() as u64);
+        assert_eq!(
+            ino(&old.join("files/lib/wine.dll")),
+            ino(&new.join("files/lib/wine.dll"))
+        );
+        assert_ne!(ino(&old.join("proton")), ino(&new.join("proton")));
+
+        // A link inside the runner stays, the one to the other runner is broken
+        fs::hard_link(new.join("files/lib/wine.dll"), new.join("files/wine.dll")).unwrap();
+        let report = undedupe_dir(&new).unwrap();
+        assert_eq!(report.files_copied, 1);
+        assert_ne!(
+            ino(&old.join("files/lib/wine.dll")),
+            ino(&new.join("files/lib/wine.dll"))
+        );
+        assert_eq!(
+            ino(&new.join("files/lib/wine.dll")),
+            ino(&new.join("files/wine.dll"))
+        );
+        assert_eq!(
+            fs::read_to_string(new.join("files/lib/wine.dll")).unwrap(),
+            "shared library"
+        );
+
+        // Nothing is shared anymore
+        assert_eq!(undedupe_dir(&new).unwrap().files_copied, 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+/// Runner metadata - inspects an installed Wine/Proton build on disk
+/// Reads the `version` file, Steam's compatibilitytool.vdf/toolmanifest.vdf and the bundled wine binaries
+use crate::runner_archive;
+use crate::runner_registry;
+use crate::runner_sources::RunnerTarget;
+use crate::vdf;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// What could be read from a runner folder
+#[derive(Debug, Default)]
+pub struct RunnerMetadata {
+    /// Version name from the `version` file or compatibilitytool.vdf
+    pub version_name: Option<String>,
+    /// Build time from the `version` file, RFC3339
+    pub version_timestamp: Option<String>,
+    pub kind: Option<RunnerTarget>,
+    pub supports_win32: bool,
+    pub supports_win64: bool,
+}
+
+/// Read a Proton `version` file
+/// Format: "timestamp VERSION_NAME", e.g. "1762104463 GE-Proton10-25"
+/// Returns (RFC3339 timestamp, version name)
+fn read_version_file(runner_path: &Path) -> Option<(Option<String>, Option<String>)> {
+    let content = fs::read_to_string(runner_path.join("version")).ok()?;
+    let mut parts = content.split_whitespace();
+
+    let timestamp = parts
+        .next()
+        .and_then(|ts| ts.parse::<i64>().ok())
+        .and_then(|ts| chrono::DateTime::<chrono::Utc>::from_timestamp(ts, 0))
+        .map(|dt| dt.to_rfc3339());
+    let name = parts.next().map(|s| s.to_string());
+
+    Some((timestamp, name))
+}
+
+/// Display name of the first tool in compatibilitytool.vdf
+fn read_compatibility_tool_name(runner_path: &Path) -> Option<String> {
+    let vdf = vdf::parse_file(&runner_path.join("compatibilitytool.vdf")).ok()?;
+    let (name, tool) = vdf
+        .get_path(&["compatibilitytools", "compat_tools"])?
+        .entries()
+        .first()?;
+
+    Some(
+        tool.get("display_name")
+            .and_then(|v| v.as_str())
+            .unwrap_or(name)
+            .to_string(),
+    )
+}
+
+/// Whether toolmanifest.vdf launches the tool through a `proton` script
+fn manifest_launches_proton(runner_path: &Path) -> bool {
+    vdf::parse_file(&runner_path.join("toolmanifest.vdf"))
+        .ok()
+        .and_then(|vdf| {
+            vdf.get_path(&["manifest", "commandline"])
+                .and_then(|v| v.as_str())
+                .map(|cmd| cmd.contains("/proton"))
+        })
+        .unwrap_or(false)
+}
+
+/// Find the folder that holds bin/ and lib/ for a runner
+/// Proton keeps them in files/ (older builds dist/), Wine builds at the top level
+fn wine_tree(runner_path: &Path) -> PathBuf {
+    ["files", "dist"]
+        .iter()
+        .map(|dir| runner_path.join(dir))
+        .find(|dir| dir.join("bin").is_dir())
+        .unwrap_or_else(|| runner_path.to_path_buf())
+}
+
+/// Check which Windows architectures a wine tree can run
+/// Returns (win32, win64)
+pub fn detect_architectures(tree: &Path) -> (bool, bool) {
+    let any_exists = |paths: &[&str]| paths.iter().any(|p| tree.join(p).exists());
+
+    let win64 = any_exists(&[
+        "bin/wine64",
+        "lib/wine/x86_64-windows",
+        "lib64/wine",
+        "lib/x86_64-linux-gnu/wine",
+    ]);
+    let win32 = any_exists(&[
+        "lib/wine/i386-windows",
+        "lib32/wine",
+        "lib/i386-linux-gnu/wine",
+    ]) || (!win64 && tree.join("bin/wine").exists());
+
+    (win32, win64)
+}
+
+/// Inspect a runner folder
+pub fn inspect_runner(runner_path: &Path) -> RunnerMetadata {
+    let (version_timestamp, version_name) = read_version_file(runner_path).unwrap_or((None, None));
+    let version_name = version_name.or_else(|| read_compatibility_tool_name(runner_path));
+
+    let kind = runner_archive::detect_runner_kind(runner_path).or_else(|| {
+        if manifest_launches_proton(runner_path) {
+            Some(RunnerTarget::Proton)
+        } else {
+            None
+        }
+    });
+
+    let (supports_win32, supports_win64) = detect_architectures(&wine_tree(runner_path));
+
+    RunnerMetadata {
+        version_name,
+        version_timestamp,
+        kind,
+        supports_win32,
+        supports_win64,
+    }
+}
+
+/// What a cached size was measured against: the runner folder's inode and the mtimes of
+/// the folder and every folder directly inside it (bin, lib, files, dist...)
+type RunnerFingerprint = (u64, Vec<(OsString, SystemTime)>);
+
+/// Sizes computed so far, one per runner folder, invalidated when its fingerprint changes
+static SIZE_CACHE: OnceLock<Mutex<HashMap<PathBuf, (RunnerFingerprint, u64)>>> = OnceLock::new();
+
+fn runner_fingerprint(runner_path: &Path) -> Option<RunnerFingerprint> {
+    let metadata = fs::metadata(runner_path).ok()?;
+    let mut modified = vec![(OsString::new(), metadata.modified().ok()?)];
+
+    for entry in fs::read_dir(runner_path).ok()?.flatten() {
+        if let Ok(child) = entry.path().symlink_metadata() {
+            if child.is_dir() {
+                modified.push((entry.file_name(), child.modified().ok()?));
+            }
+        }
+    }
+    modified.sort();
+
+    Some((metadata.ino(), modified))
+}
+
+/// Size of a runner on disk, cached because walking a runner takes a while
+pub fn runner_size(runner_path: &Path) -> u64 {
+    let fingerprint = match runner_fingerprint(runner_path) {
+        Some(fingerprint) => fingerprint,
+        None => return runner_registry::dir_size(runner_path),
+    };
+
+    let cache = SIZE_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some((cached_fingerprint, size)) = cache.lock().unwrap().get(runner_path) {
+        if *cached_fingerprint == fingerprint {
+            return *size;
+        }
+    }
+
+    let size = runner_registry::dir_size(runner_path);
+    cache
+        .lock()
+        .unwrap()
+        .insert(runner_path.to_path_buf(), (fingerprint, size));
+
+    size
+}
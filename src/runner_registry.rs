@@ -0,0 +1,152 @@
+/// Rustris runner registry - install manifest for every runner Rustris installs
+/// Stored as YAML in the Rustris data directory
+use crate::runner_sources::RunnerSource;
+use crate::rustris_paths;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Serializes read-modify-write cycles of the registry file (downloads run concurrently)
+static REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Provenance of one installed runner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunnerRecord {
+    /// Full path of the installed runner directory
+    pub path: String,
+    /// Directory name, e.g. "rustris-GE-Proton10-27"
+    pub name: String,
+    /// Source the runner was downloaded from, None for imported archives
+    pub source: Option<RunnerSource>,
+    /// Release tag for downloaded runners
+    pub release_tag: Option<String>,
+    /// Local archive path for imported runners
+    pub imported_from: Option<String>,
+    /// RFC3339 install timestamp
+    pub installed_at: String,
+    /// SHA-512 of the archive the runner was installed from
+    pub checksum: Option<String>,
+    /// The archive matched the checksum published by the source, false when it publishes none
+    #[serde(default)]
+    pub verified: bool,
+    /// Archive size in bytes
+    pub archive_size: u64,
+    /// Size of the installed runner on disk in bytes
+    pub disk_size: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RunnerRegistry {
+    #[serde(default)]
+    runners: Vec<RunnerRecord>,
+}
+
+/// Load the registry, a missing file is an empty registry
+/// An unreadable file is an error so it is never saved over and its records lost
+fn load_registry() -> Result<RunnerRegistry, String> {
+    let registry_file = match rustris_paths::rustris_runner_registry() {
+        Some(f) if f.exists() => f,
+        _ => return Ok(RunnerRegistry::default()),
+    };
+
+    let content = fs::read_to_string(&registry_file)
+        .map_err(|e| format!("Failed to read runner registry {:?}: {}", registry_file, e))?;
+
+    serde_yaml::from_str(&content).map_err(|e| {
+        format!(
+            "Runner registry {:?} is corrupt, fix or remove it: {}",
+            registry_file, e
+        )
+    })
+}
+
+fn save_registry(registry: &RunnerRegistry) -> Result<(), String> {
+    let registry_file = rustris_paths::rustris_runner_registry()
+        .ok_or("Could not get runner registry path")?;
+
+    if let Some(parent) = registry_file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create Rustris data directory: {}", e))?;
+    }
+
+    let yaml = serde_yaml::to_string(registry)
+        .map_err(|e| format!("Failed to serialize runner registry: {}", e))?;
+
+    // Write to a temp file first so a crash never leaves a truncated registry
+    let tmp_file = registry_file.with_extension("yml.tmp");
+    fs::write(&tmp_file, yaml)
+        .map_err(|e| format!("Failed to write runner registry: {}", e))?;
+    fs::rename(&tmp_file, &registry_file)
+        .map_err(|e| format!("Failed to write runner registry: {}", e))
+}
+
+/// Record a runner Rustris just installed, replacing any older record for the same path
+pub fn record_install(record: RunnerRecord) -> Result<(), String> {
+    let _guard = REGISTRY_LOCK.lock().unwrap();
+    let mut registry = load_registry()?;
+
+    registry.runners.retain(|r| r.path != record.path);
+    println!("   Registered runner {} ({} bytes on disk)", record.name, record.disk_size);
+    registry.runners.push(record);
+
+    save_registry(&registry)
+}
+
+/// Forget a runner, returns the removed record
+pub fn remove_record(path: &str) -> Result<Option<RunnerRecord>, String> {
+    let _guard = REGISTRY_LOCK.lock().unwrap();
+    let mut registry = load_registry()?;
+
+    let removed = registry.runners.iter().position(|r| r.path == path)
+        .map(|index| registry.runners.remove(index));
+
+    if removed.is_some() {
+        save_registry(&registry)?;
+    }
+
+    Ok(removed)
+}
+
+/// Get the registered runners whose directory exists
+/// Records of missing directories (e.g. an unmounted drive) are skipped but kept in the file
+fn load_records() -> Result<Vec<RunnerRecord>, String> {
+    let _guard = REGISTRY_LOCK.lock().unwrap();
+    let mut runners = load_registry()?.runners;
+
+    runners.retain(|r| Path::new(&r.path).exists());
+    Ok(runners)
+}
+
+/// Get all registered runners whose directory exists, empty if the registry is unreadable
+pub fn list_records() -> Vec<RunnerRecord> {
+    load_records().unwrap_or_else(|e| {
+        println!("Warning: {}", e);
+        Vec::new()
+    })
+}
+
+/// Total size of all files under a directory, without following symlinks
+pub fn dir_size(path: &Path) -> u64 {
+    let mut total = 0;
+
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            if let Ok(metadata) = entry.path().symlink_metadata() {
+                if metadata.is_dir() {
+                    total += dir_size(&entry.path());
+                } else {
+                    total += metadata.len();
+                }
+            }
+        }
+    }
+
+    total
+}
+
+/// List every runner installed by Rustris with its provenance
+#[tauri::command]
+pub fn get_runner_registry() -> Result<Vec<RunnerRecord>, String> {
+    load_records()
+}
@@ -0,0 +1,193 @@
+/// Runner retention - keep only the newest N Rustris-installed runners per source
+/// Pinned runners, runners used by a game and the Lutris default are never removed
+use crate::runner_registry::{self, RunnerRecord};
+use crate::runner_usage::{self, KeptRunner, PruneReport, PrunedRunner, RunnerUsage};
+use crate::runner_version;
+use crate::rustris_settings::{self, RetentionSettings};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::Emitter;
+
+/// Why a runner outside the newest N is still kept, if it is
+fn protection_reason(
+    record: &RunnerRecord,
+    usage: Option<&RunnerUsage>,
+    settings: &RetentionSettings,
+) -> Option<String> {
+    if settings.pinned.iter().any(|p| p == &record.path) {
+        return Some("Pinned".to_string());
+    }
+
+    match usage {
+        // Not found in the runner scan, leave it alone rather than guess
+        None => Some("Not found in installed runners".to_string()),
+        Some(usage) if usage.is_lutris_default => Some("Lutris default".to_string()),
+        Some(usage) if !usage.games.is_empty() => {
+            Some(format!("Used by {} game(s)", usage.games.len()))
+        }
+        Some(_) => None,
+    }
+}
+
+/// Release a record was installed from, copies in several destinations share it
+fn record_release(record: &RunnerRecord) -> String {
+    record.release_tag.clone().unwrap_or_else(|| record.name.clone())
+}
+
+/// Evaluate the retention policy against every Rustris-installed runner
+/// With `dry_run` nothing is deleted, the report lists what would be removed
+/// Runners in `installed` (and their copies in other destinations) are pinned for this run
+pub fn apply_retention(
+    settings: &RetentionSettings,
+    dry_run: bool,
+    installed: &[String],
+) -> Result<PruneReport, String> {
+    println!(
+        "Applying runner retention: keep newest {}{}",
+        settings.keep_latest,
+        if dry_run { " (dry run)" } else { "" }
+    );
+
+    let usage = runner_usage::collect_complete_runner_usage()?;
+
+    // Imported runners have no source and are never managed by the policy
+    let mut by_source: BTreeMap<String, Vec<RunnerRecord>> = BTreeMap::new();
+    let mut just_installed: Vec<(String, String)> = Vec::new();
+    for record in runner_registry::list_records() {
+        if let Some(source) = record.source {
+            if installed.contains(&record.path) {
+                just_installed.push((source.display_name().to_string(), record_release(&record)));
+            }
+            by_source
+                .entry(source.display_name().to_string())
+                .or_default()
+                .push(record);
+        }
+    }
+
+    let mut report = PruneReport {
+        dry_run,
+        removed: Vec::new(),
+        kept: Vec::new(),
+        freed_bytes: 0,
+    };
+
+    for (source, mut records) in by_source {
+        // Newest release first, install date breaks ties
+        records.sort_by(|a, b| {
+            let version_a = runner_version::parse_runner_version(a.release_tag.as_deref().unwrap_or(&a.name));
+            let version_b = runner_version::parse_runner_version(b.release_tag.as_deref().unwrap_or(&b.name));
+            version_a
+                .cmp_newest_first(&version_b)
+                .then_with(|| b.installed_at.cmp(&a.installed_at))
+        });
+
+        // A release installed into several destinations counts once towards the limit
+        let mut releases: Vec<String> = Vec::new();
+
+        for record in records {
+            let release = record_release(&record);
+            if !releases.contains(&release) {
+                releases.push(release.clone());
+            }
+            let index = releases.iter().position(|r| r == &release).unwrap_or(0);
+
+            let runner_usage = usage.iter().find(|u| u.path == record.path);
+            let display_name = runner_usage
+                .map(|u| u.display_name.clone())
+                .unwrap_or_else(|| record.name.clone());
+
+            let keep_reason = if index < settings.keep_latest {
+                Some(format!("Newest {} {}", settings.keep_latest, source))
+            } else if just_installed.contains(&(source.clone(), release)) {
+                Some("Just installed".to_string())
+            } else {
+                protection_reason(&record, runner_usage, settings)
+            };
+
+            if let Some(reason) = keep_reason {
+                report.kept.push(KeptRunner {
+                    path: record.path,
+                    display_name,
+                    reason,
+                });
+                continue;
+            }
+
+            let size_bytes = runner_registry::dir_size(Path::new(&record.path));
+
+            if !dry_run {
+                if let Err(e) = crate::proton_commands::remove_runner_dir(&record.path) {
+                    println!("   Failed to remove {}: {}", display_name, e);
+                    report.kept.push(KeptRunner {
+                        path: record.path,
+                        display_name,
+                        reason: format!("Failed to delete: {}", e),
+                    });
+                    continue;
+                }
+            }
+
+            println!("   {} {} ({} bytes)", if dry_run { "Would remove" } else { "Removed" }, display_name, size_bytes);
+            report.freed_bytes += size_bytes;
+            report.removed.push(PrunedRunner {
+                path: record.path,
+                display_name,
+                size_bytes,
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+/// Run the retention policy after a successful download, if it is enabled
+/// The runner just installed at `installed` is never removed, even when it is an older release
+/// Emits a "runner-retention" event when anything was removed
+pub async fn apply_after_install(app_handle: &tauri::AppHandle, installed: String) {
+    let settings = rustris_settings::current_settings().retention;
+    if !settings.enabled {
+        return;
+    }
+
+    let result = tokio::task::spawn_blocking(move || apply_retention(&settings, false, &[installed]))
+        .await
+        .map_err(|e| format!("Retention task failed: {}", e))
+        .and_then(|r| r);
+
+    match result {
+        Ok(report) if !report.removed.is_empty() => {
+            let _ = app_handle.emit("runner-retention", &report);
+        }
+        Ok(_) => {}
+        Err(e) => println!("Warning: runner retention failed: {}", e),
+    }
+}
+
+/// Apply the retention policy now, regardless of whether it is enabled
+#[tauri::command]
+pub async fn apply_runner_retention(dry_run: bool) -> Result<PruneReport, String> {
+    let settings = rustris_settings::current_settings().retention;
+
+    tokio::task::spawn_blocking(move || apply_retention(&settings, dry_run, &[]))
+        .await
+        .map_err(|e| format!("Retention task failed: {}", e))?
+}
+
+/// Pin a runner so retention never removes it
+#[tauri::command]
+pub fn pin_runner(path: String) -> Result<(), String> {
+    rustris_settings::update_settings(|settings| {
+        if !settings.retention.pinned.contains(&path) {
+            settings.retention.pinned.push(path);
+        }
+    })
+    .map(|_| ())
+}
+
+/// Unpin a runner
+#[tauri::command]
+pub fn unpin_runner(path: String) -> Result<(), String> {
+    rustris_settings::update_settings(|settings| settings.retention.pinned.retain(|p| p != &path))
+        .map(|_| ())
+}
@@ -0,0 +1,418 @@
+/// Runner sources - where Wine/Proton builds are downloaded from and how they are installed
+use crate::github_api::{self, RateLimit};
+use crate::http_client;
+use crate::rustris_paths;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Compression used by a runner archive
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFormat {
+    Gzip,
+    Xz,
+    Zstd,
+}
+
+impl ArchiveFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Gzip => ".tar.gz",
+            ArchiveFormat::Xz => ".tar.xz",
+            ArchiveFormat::Zstd => ".tar.zst",
+        }
+    }
+
+    /// Detect the compression of a file from its magic bytes
+    pub fn detect(path: &Path) -> Result<Self, String> {
+        let mut magic = [0u8; 6];
+        let mut file = std::fs::File::open(path)
+            .map_err(|e| format!("Failed to open archive: {}", e))?;
+        let read = file
+            .read(&mut magic)
+            .map_err(|e| format!("Failed to read archive: {}", e))?;
+        let magic = &magic[..read];
+
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Ok(ArchiveFormat::Gzip)
+        } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Ok(ArchiveFormat::Xz)
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Ok(ArchiveFormat::Zstd)
+        } else {
+            Err("Unsupported archive format (expected .tar.gz, .tar.xz or .tar.zst)".to_string())
+        }
+    }
+
+    /// Wrap a reader with the matching decompressor
+    pub fn decoder<'a, R: Read + 'a>(&self, reader: R) -> Result<Box<dyn Read + 'a>, String> {
+        match self {
+            ArchiveFormat::Gzip => Ok(Box::new(flate2::read::GzDecoder::new(reader))),
+            ArchiveFormat::Xz => Ok(Box::new(xz2::read::XzDecoder::new(reader))),
+            ArchiveFormat::Zstd => zstd::stream::read::Decoder::new(reader)
+                .map(|d| Box::new(d) as Box<dyn Read>)
+                .map_err(|e| format!("Failed to create zstd decoder: {}", e)),
+        }
+    }
+}
+
+/// Lutris runners directory a runner gets installed into
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RunnerTarget {
+    /// ~/.local/share/lutris/runners/proton
+    Proton,
+    /// ~/.local/share/lutris/runners/wine
+    Wine,
+}
+
+impl RunnerTarget {
+    pub fn dir(&self) -> Option<PathBuf> {
+        match self {
+            RunnerTarget::Proton => rustris_paths::lutris_proton_dir(),
+            RunnerTarget::Wine => rustris_paths::lutris_wine_dir(),
+        }
+    }
+}
+
+/// Where a downloaded runner gets installed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InstallDestination {
+    /// The Lutris runners directory for the source (runners/proton or runners/wine)
+    Lutris,
+    /// One of the Steam compatibilitytools.d directories
+    Steam { path: String },
+}
+
+impl InstallDestination {
+    /// Resolve the directory to install into, checking the destination suits the source
+    pub fn dir(&self, source: RunnerSource) -> Result<PathBuf, String> {
+        match self {
+            InstallDestination::Lutris => source
+                .install_target()
+                .dir()
+                .ok_or_else(|| "Could not get Lutris runner directory".to_string()),
+            InstallDestination::Steam { path } => {
+                if source.install_target() != RunnerTarget::Proton {
+                    return Err(format!("{} builds cannot be used by Steam", source.display_name()));
+                }
+
+                let dir = PathBuf::from(path);
+                if !rustris_paths::steam_compat_tools_dirs().iter().any(|(d, _)| *d == dir) {
+                    return Err(format!("{} is not a Steam compatibility tools directory", path));
+                }
+
+                Ok(dir)
+            }
+        }
+    }
+}
+
+/// Where a runner source publishes its list of releases
+pub enum ReleaseIndex {
+    /// GitHub releases of `owner/repo`
+    GitHub {
+        owner: &'static str,
+        repo: &'static str,
+    },
+    /// Lutris's own runner index (lutris.net/api/runners/{runner})
+    Lutris { runner: &'static str },
+}
+
+/// A known source of Wine/Proton runner builds
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum RunnerSource {
+    /// GloriousEggroll's GE-Proton
+    GeProton,
+    /// GloriousEggroll's Wine-GE (Lutris builds)
+    WineGe,
+    /// Kron4ek's vanilla Wine builds
+    Kron4ekWine,
+    /// Frogging-Family Proton-tkg
+    ProtonTkg,
+    /// Wine builds published in Lutris's runner index
+    LutrisWine,
+}
+
+impl RunnerSource {
+    pub fn all() -> &'static [RunnerSource] {
+        &[
+            RunnerSource::GeProton,
+            RunnerSource::WineGe,
+            RunnerSource::Kron4ekWine,
+            RunnerSource::ProtonTkg,
+            RunnerSource::LutrisWine,
+        ]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            RunnerSource::GeProton => "GE-Proton",
+            RunnerSource::WineGe => "Wine-GE",
+            RunnerSource::Kron4ekWine => "Kron4ek Wine",
+            RunnerSource::ProtonTkg => "Proton-tkg",
+            RunnerSource::LutrisWine => "Lutris Wine",
+        }
+    }
+
+    pub fn release_index(&self) -> ReleaseIndex {
+        match self {
+            RunnerSource::GeProton => ReleaseIndex::GitHub {
+                owner: "GloriousEggroll",
+                repo: "proton-ge-custom",
+            },
+            RunnerSource::WineGe => ReleaseIndex::GitHub {
+                owner: "GloriousEggroll",
+                repo: "wine-ge-custom",
+            },
+            RunnerSource::Kron4ekWine => ReleaseIndex::GitHub {
+                owner: "Kron4ek",
+                repo: "Wine-Builds",
+            },
+            RunnerSource::ProtonTkg => ReleaseIndex::GitHub {
+                owner: "Frogging-Family",
+                repo: "wine-tkg-git",
+            },
+            RunnerSource::LutrisWine => ReleaseIndex::Lutris { runner: "wine" },
+        }
+    }
+
+    pub fn archive_format(&self) -> ArchiveFormat {
+        match self {
+            RunnerSource::GeProton => ArchiveFormat::Gzip,
+            RunnerSource::WineGe => ArchiveFormat::Xz,
+            RunnerSource::Kron4ekWine => ArchiveFormat::Xz,
+            RunnerSource::ProtonTkg => ArchiveFormat::Zstd,
+            RunnerSource::LutrisWine => ArchiveFormat::Xz,
+        }
+    }
+
+    pub fn install_target(&self) -> RunnerTarget {
+        match self {
+            RunnerSource::GeProton | RunnerSource::ProtonTkg => RunnerTarget::Proton,
+            RunnerSource::WineGe | RunnerSource::Kron4ekWine | RunnerSource::LutrisWine => {
+                RunnerTarget::Wine
+            }
+        }
+    }
+
+    /// Whether every release of this source has a `.sha512sum` asset
+    /// A missing checksum is then an error rather than something to skip
+    pub fn publishes_checksums(&self) -> bool {
+        matches!(self, RunnerSource::GeProton | RunnerSource::WineGe)
+    }
+
+    /// Whether a release asset is this source's runner archive for `tag_name`
+    /// Only used for GitHub sources, the Lutris index has exactly one URL per version
+    pub fn matches_asset(&self, tag_name: &str, asset_name: &str) -> bool {
+        if !asset_name.ends_with(self.archive_format().extension()) {
+            return false;
+        }
+
+        match self {
+            RunnerSource::GeProton => true,
+            // e.g. wine-lutris-GE-Proton8-26-x86_64.tar.xz
+            RunnerSource::WineGe => asset_name.starts_with("wine-lutris-"),
+            // Vanilla 64-bit build, e.g. wine-10.0-amd64.tar.xz (not staging/tkg/wow64 variants)
+            RunnerSource::Kron4ekWine => asset_name == format!("wine-{}-amd64.tar.xz", tag_name),
+            RunnerSource::ProtonTkg => asset_name.starts_with("proton"),
+            RunnerSource::LutrisWine => true,
+        }
+    }
+
+    /// Name of the folder the runner is installed as
+    /// Always uses the rustris- prefix to distinguish from Lutris-managed versions
+    /// Tags come from the release index, so ones that are not a plain folder name are refused
+    pub fn install_dir_name(&self, tag_name: &str) -> Result<String, String> {
+        let invalid = tag_name.is_empty()
+            || tag_name.contains('/')
+            || tag_name.contains("..")
+            || tag_name.contains('\0');
+        if invalid {
+            return Err(format!("Invalid release tag '{}'", tag_name));
+        }
+
+        Ok(match self {
+            RunnerSource::GeProton => format!("rustris-{}", tag_name),
+            RunnerSource::WineGe => format!("rustris-lutris-{}-x86_64", tag_name),
+            RunnerSource::Kron4ekWine => format!("rustris-wine-{}-amd64", tag_name),
+            RunnerSource::ProtonTkg => format!("rustris-proton-tkg-{}", tag_name),
+            RunnerSource::LutrisWine => format!("rustris-{}-x86_64", tag_name),
+        })
+    }
+}
+
+/// A downloadable runner release from any source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunnerRelease {
+    pub tag_name: String,
+    pub name: String,
+    pub published_at: String,
+    pub download_url: String,
+    pub size_mb: f64,
+    /// URL of the published `.sha512sum` asset, if the release has one
+    pub checksum_url: Option<String>,
+    /// Release notes (Markdown), empty when the source publishes none
+    #[serde(default)]
+    pub body: String,
+}
+
+/// Releases of a runner source
+#[derive(Debug, Serialize)]
+pub struct ReleaseList {
+    pub releases: Vec<RunnerRelease>,
+    /// Set when GitHub's rate limit was hit and the releases are the last cached list
+    pub rate_limited: Option<RateLimit>,
+}
+
+/// Fetch the release list of a runner source
+pub async fn fetch_releases(source: RunnerSource) -> Result<ReleaseList, String> {
+    match source.release_index() {
+        ReleaseIndex::GitHub { owner, repo } => fetch_github_releases(source, owner, repo).await,
+        ReleaseIndex::Lutris { runner } => Ok(ReleaseList {
+            releases: fetch_lutris_releases(runner).await?,
+            rate_limited: None,
+        }),
+    }
+}
+
+/// Build releases from a GitHub repository's release list
+async fn fetch_github_releases(
+    source: RunnerSource,
+    owner: &str,
+    repo: &str,
+) -> Result<ReleaseList, String> {
+    let listing = github_api::get_all_pages(&format!("/repos/{}/{}/releases", owner, repo)).await?;
+
+    let mut runner_releases: Vec<RunnerRelease> = Vec::new();
+
+    for release in &listing.items {
+        let tag_name = release["tag_name"].as_str().unwrap_or("").to_string();
+        let name = release["name"].as_str().unwrap_or("").to_string();
+        let published_at = release["published_at"].as_str().unwrap_or("").to_string();
+        let body = release["body"].as_str().unwrap_or("").to_string();
+
+        let assets = match release["assets"].as_array() {
+            Some(assets) => assets,
+            None => continue,
+        };
+
+        // Find the runner archive asset
+        let archive_asset = assets.iter().find(|asset| {
+            asset["name"]
+                .as_str()
+                .map(|n| source.matches_asset(&tag_name, n))
+                .unwrap_or(false)
+        });
+
+        if let Some(asset) = archive_asset {
+            // The checksum asset is named after the archive, with or without its extension
+            // e.g. "GE-Proton10-27.sha512sum" for "GE-Proton10-27.tar.gz"
+            let archive_name = asset["name"].as_str().unwrap_or("");
+            let archive_stem = archive_name
+                .strip_suffix(source.archive_format().extension())
+                .unwrap_or(archive_name);
+            let checksum_names = [
+                format!("{}.sha512sum", archive_name),
+                format!("{}.sha512sum", archive_stem),
+            ];
+            let checksum_url = assets
+                .iter()
+                .find(|a| {
+                    a["name"].as_str().map(|n| checksum_names.iter().any(|c| c == n)).unwrap_or(false)
+                })
+                .and_then(|a| a["browser_download_url"].as_str())
+                .map(|url| url.to_string());
+
+            let download_url = asset["browser_download_url"]
+                .as_str()
+                .unwrap_or("")
+                .to_string();
+            let size_bytes = asset["size"].as_u64().unwrap_or(0);
+            let size_mb = size_bytes as f64 / 1024.0 / 1024.0;
+
+            runner_releases.push(RunnerRelease {
+                tag_name,
+                name,
+                published_at,
+                download_url,
+                size_mb,
+                checksum_url,
+                body,
+            });
+        }
+    }
+
+    Ok(ReleaseList {
+        releases: runner_releases,
+        rate_limited: listing.rate_limited,
+    })
+}
+
+/// Build releases from Lutris's runner index
+/// The index lists every version with a direct download URL but no sizes or checksums
+async fn fetch_lutris_releases(runner: &str) -> Result<Vec<RunnerRelease>, String> {
+    let url = format!("{}/runners/{}", http_client::lutris_api_url(), runner);
+
+    let response = http_client::send_with_retry(http_client::api_client()?.get(&url))
+        .await
+        .map_err(|e| format!("Failed to fetch Lutris runner index: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Lutris API returned status: {}", response.status()));
+    }
+
+    let index: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    let versions = index["versions"]
+        .as_array()
+        .ok_or("Lutris runner index is missing 'versions'")?;
+
+    let releases = versions
+        .iter()
+        .filter(|v| v["architecture"].as_str() == Some("x86_64"))
+        .filter_map(|v| {
+            let version = v["version"].as_str()?.to_string();
+            let download_url = v["url"].as_str()?.to_string();
+
+            Some(RunnerRelease {
+                tag_name: version.clone(),
+                name: version,
+                published_at: String::new(),
+                download_url,
+                size_mb: 0.0,
+                checksum_url: None,
+                body: String::new(),
+            })
+        })
+        .collect();
+
+    Ok(releases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_dir_name() {
+        assert_eq!(
+            RunnerSource::GeProton.install_dir_name("GE-Proton10-27").as_deref(),
+            Ok("rustris-GE-Proton10-27")
+        );
+        assert_eq!(
+            RunnerSource::Kron4ekWine.install_dir_name("10.0").as_deref(),
+            Ok("rustris-wine-10.0-amd64")
+        );
+
+        for tag in ["", "../../evil", "GE-Proton10-27/../x", "a/b", ".."] {
+            assert!(RunnerSource::GeProton.install_dir_name(tag).is_err(), "{}", tag);
+        }
+    }
+}
@@ -0,0 +1,286 @@
+/// Runner trash - deleted runners are moved here and can be restored until they are purged
+/// The trash lives on the same filesystem as the runner so moving it is a cheap rename
+use crate::runner_registry::{self, RunnerRecord};
+use crate::rustris_paths;
+use crate::rustris_settings::{self, TrashSettings};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Serializes read-modify-write cycles of the trash manifest
+static TRASH_LOCK: Mutex<()> = Mutex::new(());
+
+/// A runner in the trash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    /// Runner folder name
+    pub name: String,
+    /// Where the runner was installed, restore moves it back here
+    pub original_path: String,
+    /// Where the runner is now
+    pub trash_path: String,
+    /// RFC3339 deletion timestamp
+    pub deleted_at: String,
+    pub size_bytes: u64,
+    /// Registry record of a Rustris-installed runner, put back on restore
+    pub record: Option<RunnerRecord>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TrashManifest {
+    #[serde(default)]
+    entries: Vec<TrashEntry>,
+}
+
+/// Load the manifest, a missing file is an empty trash
+/// An unreadable file is an error so it is never saved over and the trashed runners orphaned
+fn load_manifest() -> Result<TrashManifest, String> {
+    let manifest_file = match rustris_paths::rustris_trash_manifest() {
+        Some(f) if f.exists() => f,
+        _ => return Ok(TrashManifest::default()),
+    };
+
+    let content = fs::read_to_string(&manifest_file)
+        .map_err(|e| format!("Failed to read trash manifest {:?}: {}", manifest_file, e))?;
+
+    serde_yaml::from_str(&content).map_err(|e| {
+        format!(
+            "Trash manifest {:?} is corrupt, fix or remove it: {}",
+            manifest_file, e
+        )
+    })
+}
+
+fn save_manifest(manifest: &TrashManifest) -> Result<(), String> {
+    let manifest_file = rustris_paths::rustris_trash_manifest()
+        .ok_or("Could not get trash manifest path")?;
+
+    if let Some(parent) = manifest_file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create Rustris data directory: {}", e))?;
+    }
+
+    let yaml = serde_yaml::to_string(manifest)
+        .map_err(|e| format!("Failed to serialize trash manifest: {}", e))?;
+
+    let tmp_file = manifest_file.with_extension("yml.tmp");
+    fs::write(&tmp_file, yaml)
+        .map_err(|e| format!("Failed to write trash manifest: {}", e))?;
+    fs::rename(&tmp_file, &manifest_file)
+        .map_err(|e| format!("Failed to write trash manifest: {}", e))
+}
+
+/// Pick a trash directory on the same filesystem as the runner
+/// The Rustris trash is used when possible, otherwise a hidden folder next to the runner
+fn trash_dir_for(runner_path: &Path) -> Result<PathBuf, String> {
+    let runner_parent = runner_path.parent().ok_or("Runner path has no parent directory")?;
+    let runner_device = runner_parent
+        .metadata()
+        .map_err(|e| format!("Failed to read runner directory: {}", e))?
+        .dev();
+
+    if let Some(trash_dir) = rustris_paths::rustris_trash_dir() {
+        if fs::create_dir_all(&trash_dir).is_ok() {
+            if let Ok(metadata) = trash_dir.metadata() {
+                if metadata.dev() == runner_device {
+                    return Ok(trash_dir);
+                }
+            }
+        }
+    }
+
+    // Hidden, so runner scans skip it
+    let trash_dir = runner_parent.join(".rustris-trash");
+    fs::create_dir_all(&trash_dir)
+        .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+
+    Ok(trash_dir)
+}
+
+/// Move a runner into the trash
+/// `runner_path` must already have passed the deletion safety checks
+pub fn move_to_trash(runner_path: &Path, record: Option<RunnerRecord>) -> Result<TrashEntry, String> {
+    let name = runner_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("Runner path has no folder name")?;
+
+    let now = chrono::Local::now();
+    let id = format!("{}-{}", now.format("%Y%m%d%H%M%S%3f"), name);
+    let size_bytes = runner_registry::dir_size(runner_path);
+    let trash_path = trash_dir_for(runner_path)?.join(&id);
+
+    let entry = TrashEntry {
+        id,
+        name,
+        original_path: runner_path.to_string_lossy().to_string(),
+        trash_path: trash_path.to_string_lossy().to_string(),
+        deleted_at: now.to_rfc3339(),
+        size_bytes,
+        record,
+    };
+
+    {
+        let _guard = TRASH_LOCK.lock().unwrap();
+        let mut manifest = load_manifest()?;
+
+        fs::rename(runner_path, &trash_path)
+            .map_err(|e| format!("Failed to move runner to the trash: {}", e))?;
+
+        // A runner the manifest does not list could never be restored or purged
+        manifest.entries.push(entry.clone());
+        if let Err(e) = save_manifest(&manifest) {
+            if let Err(rollback) = fs::rename(&trash_path, runner_path) {
+                println!("   Warning: failed to move {} back: {}", entry.name, rollback);
+            }
+            return Err(e);
+        }
+    }
+
+    println!("   Moved {} to the trash ({} bytes)", entry.name, entry.size_bytes);
+
+    purge_expired(&rustris_settings::current_settings().trash);
+
+    Ok(entry)
+}
+
+/// Get the trash entries whose folder exists
+/// Entries on a missing drive are skipped but kept in the manifest
+pub fn list_entries() -> Result<Vec<TrashEntry>, String> {
+    let _guard = TRASH_LOCK.lock().unwrap();
+    let mut entries = load_manifest()?.entries;
+
+    entries.retain(|e| Path::new(&e.trash_path).exists());
+    Ok(entries)
+}
+
+/// Permanently delete trash entries matching `should_purge`, returns the number purged
+fn purge_where(should_purge: impl Fn(&TrashEntry, u64) -> bool) -> Result<usize, String> {
+    let _guard = TRASH_LOCK.lock().unwrap();
+    let mut manifest = load_manifest()?;
+
+    // Oldest first so the size limit removes the oldest entries
+    manifest.entries.sort_by(|a, b| a.deleted_at.cmp(&b.deleted_at));
+    let mut remaining_bytes: u64 = manifest.entries.iter().map(|e| e.size_bytes).sum();
+    let mut purged = 0;
+
+    let mut kept = Vec::new();
+    for entry in manifest.entries.drain(..) {
+        if !should_purge(&entry, remaining_bytes) {
+            kept.push(entry);
+            continue;
+        }
+
+        let trash_path = Path::new(&entry.trash_path);
+        if trash_path.exists() {
+            if let Err(e) = fs::remove_dir_all(trash_path) {
+                println!("   Warning: failed to purge {}: {}", entry.name, e);
+                kept.push(entry);
+                continue;
+            }
+        }
+
+        println!("   Purged {} from the trash", entry.name);
+        remaining_bytes = remaining_bytes.saturating_sub(entry.size_bytes);
+        purged += 1;
+    }
+
+    manifest.entries = kept;
+    save_manifest(&manifest)?;
+
+    Ok(purged)
+}
+
+/// Purge entries that are past the age limit, then the oldest ones until the trash fits the size limit
+pub fn purge_expired(settings: &TrashSettings) {
+    let now = chrono::Local::now();
+    let max_age = chrono::Duration::days(settings.max_age_days as i64);
+    let max_bytes = settings.max_size_mb * 1024 * 1024;
+
+    let result = purge_where(|entry, remaining_bytes| {
+        let too_old = settings.max_age_days > 0
+            && chrono::DateTime::parse_from_rfc3339(&entry.deleted_at)
+                .map(|deleted_at| now.signed_duration_since(deleted_at) > max_age)
+                .unwrap_or(false);
+        let too_big = settings.max_size_mb > 0 && remaining_bytes > max_bytes;
+
+        too_old || too_big
+    });
+
+    if let Err(e) = result {
+        println!("Warning: failed to purge the runner trash: {}", e);
+    }
+}
+
+/// List runners in the trash
+#[tauri::command]
+pub async fn list_runner_trash() -> Result<Vec<TrashEntry>, String> {
+    tokio::task::spawn_blocking(list_entries)
+        .await
+        .map_err(|e| format!("Trash task failed: {}", e))?
+}
+
+/// Move a trashed runner back to where it was installed
+#[tauri::command]
+pub async fn restore_runner_from_trash(id: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || restore_entry(&id))
+        .await
+        .map_err(|e| format!("Trash task failed: {}", e))?
+}
+
+fn restore_entry(id: &str) -> Result<String, String> {
+    let _guard = TRASH_LOCK.lock().unwrap();
+    let mut manifest = load_manifest()?;
+
+    let index = manifest
+        .entries
+        .iter()
+        .position(|e| e.id == id)
+        .ok_or_else(|| format!("No runner with id {} in the trash", id))?;
+    let entry = &manifest.entries[index];
+
+    println!("Restoring {} from the trash", entry.name);
+
+    let original_path = Path::new(&entry.original_path);
+    if original_path.exists() {
+        return Err(format!("{} already exists, remove it first", entry.original_path));
+    }
+    if let Some(parent) = original_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create runner directory: {}", e))?;
+    }
+
+    fs::rename(&entry.trash_path, original_path)
+        .map_err(|e| format!("Failed to restore runner: {}", e))?;
+
+    let entry = manifest.entries.remove(index);
+    if let Err(e) = save_manifest(&manifest) {
+        if let Err(rollback) = fs::rename(&entry.original_path, &entry.trash_path) {
+            println!("   Warning: failed to move {} back to the trash: {}", entry.name, rollback);
+        }
+        return Err(e);
+    }
+
+    // The runner is back in place, a registry failure is only logged
+    if let Some(record) = entry.record {
+        if let Err(e) = runner_registry::record_install(record) {
+            println!("   Warning: failed to update runner registry: {}", e);
+        }
+    }
+
+    println!("   Restored to {}", entry.original_path);
+    Ok(entry.original_path)
+}
+
+/// Permanently delete everything in the trash, returns the number of runners purged
+#[tauri::command]
+pub async fn empty_runner_trash() -> Result<usize, String> {
+    println!("Emptying runner trash");
+
+    tokio::task::spawn_blocking(|| purge_where(|_, _| true))
+        .await
+        .map_err(|e| format!("Trash task failed: {}", e))?
+}
@@ -0,0 +1,280 @@
+/// Runner usage - which games use each installed Wine/Proton runner, and pruning of unused ones
+use crate::lutris_cli;
+use crate::lutris_commands::{self, WineVersionInfo};
+use crate::lutris_db::LutrisDatabase;
+use crate::runner_registry;
+use crate::rustris_paths;
+use serde::Serialize;
+use std::path::Path;
+
+/// A game that references a runner
+#[derive(Debug, Clone, Serialize)]
+pub struct RunnerGame {
+    pub slug: String,
+    pub name: String,
+}
+
+/// Usage of one installed runner
+#[derive(Debug, Clone, Serialize)]
+pub struct RunnerUsage {
+    pub path: String,
+    pub display_name: String,
+    pub games: Vec<RunnerGame>,
+    /// Runner is the Lutris global default (runners/wine.yml)
+    pub is_lutris_default: bool,
+    /// Runner was installed by Rustris
+    pub is_managed: bool,
+}
+
+/// A runner removed (or that would be removed) by a prune
+#[derive(Debug, Clone, Serialize)]
+pub struct PrunedRunner {
+    pub path: String,
+    pub display_name: String,
+    pub size_bytes: u64,
+}
+
+/// A runner a prune left in place, with why
+#[derive(Debug, Clone, Serialize)]
+pub struct KeptRunner {
+    pub path: String,
+    pub display_name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PruneReport {
+    pub dry_run: bool,
+    pub removed: Vec<PrunedRunner>,
+    pub kept: Vec<KeptRunner>,
+    pub freed_bytes: u64,
+}
+
+/// Check whether a runner reference (a runner folder or an executable inside one) points at `runner_path`
+pub fn references_runner(reference: &str, runner_path: &str) -> bool {
+    Path::new(reference).starts_with(runner_path)
+}
+
+/// Check whether a runner lives directly in a Lutris runner directory
+/// Only those are ever removed, Steam and system runners are left alone
+pub fn is_in_lutris_runner_dir(runner_path: &str) -> bool {
+    let parent = Path::new(runner_path).parent();
+
+    [rustris_paths::lutris_wine_dir(), rustris_paths::lutris_proton_dir()]
+        .into_iter()
+        .flatten()
+        .any(|dir| parent == Some(dir.as_path()))
+}
+
+/// Runner references of the installed Wine games
+pub struct GameReferences {
+    /// (game, runner folder)
+    pub references: Vec<(RunnerGame, String)>,
+    /// (game, why) for games whose runner could not be worked out, they may use any runner
+    pub unresolved: Vec<(RunnerGame, String)>,
+}
+
+/// Read the runner every installed Wine game uses
+pub fn scan_game_runners() -> Result<GameReferences, String> {
+    let db = LutrisDatabase::new()?;
+    let db_games = db.get_installed_games()?;
+
+    let mut scan = GameReferences {
+        references: Vec::new(),
+        unresolved: Vec::new(),
+    };
+
+    for g in db_games.iter().filter(|g| g.runner.as_deref() == Some("wine")) {
+        let slug = g.slug.clone().unwrap_or_default();
+        let game = RunnerGame {
+            name: g.name.clone().unwrap_or_else(|| slug.clone()),
+            slug,
+        };
+
+        let runner = g
+            .configpath
+            .as_deref()
+            .ok_or_else(|| "no config path in the Lutris database".to_string())
+            .and_then(lutris_cli::game_wine_runner);
+
+        match runner {
+            Ok(Some(runner)) => scan.references.push((game, runner)),
+            // Runs with the Lutris default
+            Ok(None) => {}
+            Err(e) => scan.unresolved.push((game, e)),
+        }
+    }
+
+    Ok(scan)
+}
+
+/// Get the runner reference of every installed game as (game, runner folder)
+/// Games whose runner can't be worked out are left out, only for display
+pub fn game_runner_references() -> Result<Vec<(RunnerGame, String)>, String> {
+    Ok(scan_game_runners()?.references)
+}
+
+/// Error listing games whose runner is unknown, nothing may be removed while there are any
+fn unresolved_error(unresolved: &[(RunnerGame, String)]) -> String {
+    let games: Vec<String> = unresolved
+        .iter()
+        .map(|(game, reason)| format!("{}: {}", game.name, reason))
+        .collect();
+
+    format!(
+        "Cannot tell which runner {} game(s) use, fix their config first. {}",
+        unresolved.len(),
+        games.join("; ")
+    )
+}
+
+/// Get the games that reference a runner
+/// Fails when any game's runner is unknown, since it might be this one
+pub fn games_using_runner(runner_path: &str) -> Result<Vec<RunnerGame>, String> {
+    let scan = scan_game_runners()?;
+    if !scan.unresolved.is_empty() {
+        return Err(unresolved_error(&scan.unresolved));
+    }
+
+    Ok(scan
+        .references
+        .into_iter()
+        .filter(|(_, reference)| references_runner(reference, runner_path))
+        .map(|(game, _)| game)
+        .collect())
+}
+
+/// Cross-reference every game config and the Lutris default against the installed runners
+/// Also returns what could not be resolved: while that is not empty, runners that look unused may not be
+pub fn collect_runner_usage() -> Result<(Vec<RunnerUsage>, Vec<String>), String> {
+    let versions: Vec<WineVersionInfo> = lutris_commands::list_wine_versions(false)?;
+    let GameReferences {
+        references,
+        unresolved,
+    } = scan_game_runners()?;
+
+    let mut problems = Vec::new();
+    if !unresolved.is_empty() {
+        problems.push(unresolved_error(&unresolved));
+    }
+    let default_wine = lutris_cli::lutris_default_wine_runner().unwrap_or_else(|e| {
+        problems.push(format!("Cannot tell which runner is the Lutris default. {}", e));
+        None
+    });
+
+    let usage = versions
+        .into_iter()
+        .map(|version| {
+            let games = references
+                .iter()
+                .filter(|(_, reference)| references_runner(reference, &version.path))
+                .map(|(game, _)| game.clone())
+                .collect();
+
+            let is_lutris_default = default_wine
+                .as_deref()
+                .map(|default| references_runner(default, &version.path))
+                .unwrap_or(false);
+
+            RunnerUsage {
+                is_managed: version.managed.is_some(),
+                path: version.path,
+                display_name: version.display_name,
+                games,
+                is_lutris_default,
+            }
+        })
+        .collect();
+
+    Ok((usage, problems))
+}
+
+/// Runner usage to decide deletions on, an error when any game or the default is unresolved
+pub fn collect_complete_runner_usage() -> Result<Vec<RunnerUsage>, String> {
+    let (usage, problems) = collect_runner_usage()?;
+    if !problems.is_empty() {
+        return Err(format!("Not removing any runner. {}", problems.join(" ")));
+    }
+
+    Ok(usage)
+}
+
+/// Remove every runner no game and no default uses
+/// With `dry_run` nothing is deleted, the report lists what would be removed
+pub fn prune_unused(dry_run: bool) -> Result<PruneReport, String> {
+    println!("Pruning unused runners{}", if dry_run { " (dry run)" } else { "" });
+
+    let mut report = PruneReport {
+        dry_run,
+        removed: Vec::new(),
+        kept: Vec::new(),
+        freed_bytes: 0,
+    };
+
+    for runner in collect_complete_runner_usage()? {
+        let keep_reason = if runner.is_lutris_default {
+            Some("Lutris default".to_string())
+        } else if !runner.games.is_empty() {
+            Some(format!("Used by {} game(s)", runner.games.len()))
+        } else if !is_in_lutris_runner_dir(&runner.path) {
+            Some("Not in a Lutris runner directory".to_string())
+        } else {
+            None
+        };
+
+        if let Some(reason) = keep_reason {
+            report.kept.push(KeptRunner {
+                path: runner.path,
+                display_name: runner.display_name,
+                reason,
+            });
+            continue;
+        }
+
+        let size_bytes = runner_registry::dir_size(Path::new(&runner.path));
+
+        if !dry_run {
+            if let Err(e) = crate::proton_commands::remove_runner_dir(&runner.path) {
+                println!("   Failed to remove {}: {}", runner.display_name, e);
+                report.kept.push(KeptRunner {
+                    path: runner.path,
+                    display_name: runner.display_name,
+                    reason: format!("Failed to delete: {}", e),
+                });
+                continue;
+            }
+        }
+
+        println!("   {} {} ({} bytes)", if dry_run { "Would remove" } else { "Removed" }, runner.display_name, size_bytes);
+        report.freed_bytes += size_bytes;
+        report.removed.push(PrunedRunner {
+            path: runner.path,
+            display_name: runner.display_name,
+            size_bytes,
+        });
+    }
+
+    println!("   {} runner(s), {} bytes", report.removed.len(), report.freed_bytes);
+    Ok(report)
+}
+
+/// Get every installed runner with the games that use it
+#[tauri::command]
+pub async fn get_runner_usage() -> Result<Vec<RunnerUsage>, String> {
+    let (usage, problems) = tokio::task::spawn_blocking(collect_runner_usage)
+        .await
+        .map_err(|e| format!("Runner usage task failed: {}", e))??;
+
+    for problem in problems {
+        println!("Warning: {}", problem);
+    }
+    Ok(usage)
+}
+
+/// Remove runners that no game and not the Lutris default uses
+#[tauri::command]
+pub async fn prune_unused_runners(dry_run: bool) -> Result<PruneReport, String> {
+    tokio::task::spawn_blocking(move || prune_unused(dry_run))
+        .await
+        .map_err(|e| format!("Prune task failed: {}", e))?
+}
@@ -0,0 +1,158 @@
+/// Runner version parsing - recognises the Wine/Proton naming schemes and orders versions naturally
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Family a Wine/Proton build belongs to, used to group the version dropdown
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum RunnerFamily {
+    GeProton,
+    WineGe,
+    Proton,
+    LutrisWine,
+    Other,
+    SystemWine,
+}
+
+/// A parsed runner name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunnerVersion {
+    pub family: RunnerFamily,
+    /// Numeric version components, e.g. GE-Proton10-27 -> [10, 27]
+    pub numbers: Vec<u64>,
+    /// Rolling builds such as "Proton - Experimental" sort above every numbered release
+    pub experimental: bool,
+}
+
+/// Architecture suffixes whose digits are not part of the version
+const ARCH_SUFFIXES: [&str; 4] = ["x86_64", "amd64", "i386", "i686"];
+
+fn detect_family(name: &str) -> RunnerFamily {
+    if name == "system wine" || name == "system" {
+        RunnerFamily::SystemWine
+    } else if name.starts_with("wine-ge") || name.starts_with("lutris-ge") || name.starts_with("wine-lutris-ge") {
+        // Wine-GE releases are named "lutris-GE-Proton8-26-x86_64" or "wine-ge-8-26"
+        RunnerFamily::WineGe
+    } else if name.contains("ge-proton") || (name.starts_with("proton-") && name.contains("-ge")) {
+        // "GE-Proton10-27" and the older "Proton-6.5-GE-2"
+        RunnerFamily::GeProton
+    } else if name.contains("tkg") {
+        RunnerFamily::Other
+    } else if name.starts_with("proton") {
+        // Valve builds: "Proton 9.0", "Proton - Experimental", "Proton Hotfix"
+        RunnerFamily::Proton
+    } else if name.contains("lutris") {
+        // "lutris-7.2-2-x86_64", "wine-lutris-7.2-2", "lutris-fshack-7.2"
+        RunnerFamily::LutrisWine
+    } else {
+        RunnerFamily::Other
+    }
+}
+
+/// Parse a runner name (version file name or folder name)
+pub fn parse_runner_version(name: &str) -> RunnerVersion {
+    let lower = name.trim().to_lowercase();
+    let lower = lower.strip_prefix("rustris-").unwrap_or(&lower);
+
+    let mut version_part = lower.to_string();
+    for arch in ARCH_SUFFIXES {
+        version_part = version_part.replace(arch, "");
+    }
+
+    let numbers = version_part
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.parse().ok())
+        .collect();
+
+    RunnerVersion {
+        family: detect_family(lower),
+        numbers,
+        experimental: lower.contains("experimental") || lower.contains("hotfix"),
+    }
+}
+
+impl RunnerVersion {
+    /// Order two versions of the same family, newer first
+    pub fn cmp_newest_first(&self, other: &Self) -> Ordering {
+        other
+            .experimental
+            .cmp(&self.experimental)
+            .then_with(|| other.numbers.cmp(&self.numbers))
+    }
+}
+
+/// Order runner names by family, then newest first within a family
+pub fn compare_runner_names(a: &str, b: &str) -> Ordering {
+    let version_a = parse_runner_version(a);
+    let version_b = parse_runner_version(b);
+
+    version_a
+        .family
+        .cmp(&version_b.family)
+        .then_with(|| version_a.cmp_newest_first(&version_b))
+        .then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_families() {
+        assert_eq!(parse_runner_version("GE-Proton10-27").family, RunnerFamily::GeProton);
+        assert_eq!(parse_runner_version("rustris-GE-Proton9-27").family, RunnerFamily::GeProton);
+        assert_eq!(parse_runner_version("Proton-6.5-GE-2").family, RunnerFamily::GeProton);
+        assert_eq!(parse_runner_version("wine-ge-8-26").family, RunnerFamily::WineGe);
+        assert_eq!(parse_runner_version("lutris-GE-Proton8-26-x86_64").family, RunnerFamily::WineGe);
+        assert_eq!(parse_runner_version("Proton 9.0").family, RunnerFamily::Proton);
+        assert_eq!(parse_runner_version("Proton - Experimental").family, RunnerFamily::Proton);
+        assert_eq!(parse_runner_version("lutris-7.2-2-x86_64").family, RunnerFamily::LutrisWine);
+        assert_eq!(parse_runner_version("System Wine").family, RunnerFamily::SystemWine);
+        assert_eq!(parse_runner_version("wine-9.0-amd64").family, RunnerFamily::Other);
+        assert_eq!(parse_runner_version("rustris-proton-tkg-9.0").family, RunnerFamily::Other);
+    }
+
+    #[test]
+    fn test_numbers_ignore_arch() {
+        assert_eq!(parse_runner_version("lutris-GE-Proton8-26-x86_64").numbers, vec![8, 26]);
+        assert_eq!(parse_runner_version("wine-9.0-amd64").numbers, vec![9, 0]);
+        assert_eq!(parse_runner_version("Proton 8.0-5").numbers, vec![8, 0, 5]);
+    }
+
+    #[test]
+    fn test_natural_order_newest_first() {
+        let mut names = vec![
+            "GE-Proton9-27",
+            "GE-Proton10-1",
+            "GE-Proton9-3",
+            "GE-Proton10-27",
+        ];
+        names.sort_by(|a, b| compare_runner_names(a, b));
+        assert_eq!(names, vec!["GE-Proton10-27", "GE-Proton10-1", "GE-Proton9-27", "GE-Proton9-3"]);
+    }
+
+    #[test]
+    fn test_grouped_by_family() {
+        let mut names = vec![
+            "System Wine",
+            "wine-ge-8-26",
+            "Proton 9.0",
+            "GE-Proton9-27",
+            "Proton - Experimental",
+            "lutris-7.2-2-x86_64",
+        ];
+        names.sort_by(|a, b| compare_runner_names(a, b));
+        assert_eq!(
+            names,
+            vec![
+                "GE-Proton9-27",
+                "wine-ge-8-26",
+                "Proton - Experimental",
+                "Proton 9.0",
+                "lutris-7.2-2-x86_64",
+                "System Wine",
+            ]
+        );
+    }
+}
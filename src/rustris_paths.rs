@@ -49,6 +49,25 @@ pub fn lutris_proton_dir() -> Option<PathBuf> {
     lutris_runners_dir().map(|d| d.join("proton"))
 }
 
+/// Get the Flatpak Lutris data directory
+/// Returns: ~/.var/app/net.lutris.Lutris/data/lutris
+pub fn lutris_flatpak_data_dir() -> Option<PathBuf> {
+    home_dir().map(|h| h.join(".var/app/net.lutris.Lutris/data/lutris"))
+}
+
+/// Get every directory runners may be deleted from
+/// Lutris wine/proton runner directories, for both system and Flatpak Lutris
+pub fn lutris_runner_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+
+    for data_dir in [lutris_data_dir(), lutris_flatpak_data_dir()].into_iter().flatten() {
+        roots.push(data_dir.join("runners/wine"));
+        roots.push(data_dir.join("runners/proton"));
+    }
+
+    roots
+}
+
 /// Get the Lutris coverart directory
 /// Returns: ~/.local/share/lutris/coverart
 pub fn lutris_coverart_dir() -> Option<PathBuf> {
@@ -85,6 +104,24 @@ pub fn rustris_crashes_dir() -> Option<PathBuf> {
     rustris_data_dir().map(|d| d.join("crashes"))
 }
 
+/// Get the Rustris downloads directory (partial and completed runner archives)
+/// Returns: ~/.local/share/rustris/downloads
+pub fn rustris_downloads_dir() -> Option<PathBuf> {
+    rustris_data_dir().map(|d| d.join("downloads"))
+}
+
+/// Get the Rustris trash directory (deleted runners, until purged)
+/// Returns: ~/.local/share/rustris/trash
+pub fn rustris_trash_dir() -> Option<PathBuf> {
+    rustris_data_dir().map(|d| d.join("trash"))
+}
+
+/// Get the GitHub API response cache directory
+/// Returns: ~/.local/share/rustris/github_cache
+pub fn rustris_github_cache_dir() -> Option<PathBuf> {
+    rustris_data_dir().map(|d| d.join("github_cache"))
+}
+
 // ============================================================================
 // Specific File Paths
 // ============================================================================
@@ -102,6 +139,12 @@ pub fn lutris_wine_config() -> Option<PathBuf> {
     lutris_runner_config("wine")
 }
 
+/// Get the Lutris system config file (options shared by every game)
+/// Returns: ~/.local/share/lutris/system.yml
+pub fn lutris_system_config() -> Option<PathBuf> {
+    lutris_data_dir().map(|d| d.join("system.yml"))
+}
+
 /// Get a Lutris game config file by config name
 /// Returns: ~/.local/share/lutris/games/{config_name}.yml
 pub fn lutris_game_config(config_name: &str) -> Option<PathBuf> {
@@ -120,6 +163,30 @@ pub fn lutris_database() -> Option<PathBuf> {
     lutris_data_dir().map(|d| d.join("pga.db"))
 }
 
+/// Get the Rustris settings file
+/// Returns: ~/.local/share/rustris/settings.yml
+pub fn rustris_settings_file() -> Option<PathBuf> {
+    rustris_data_dir().map(|d| d.join("settings.yml"))
+}
+
+/// Get the Rustris runner registry (install manifest of Rustris-installed runners)
+/// Returns: ~/.local/share/rustris/runners.yml
+pub fn rustris_runner_registry() -> Option<PathBuf> {
+    rustris_data_dir().map(|d| d.join("runners.yml"))
+}
+
+/// Get the Rustris trash manifest
+/// Returns: ~/.local/share/rustris/trash.yml
+pub fn rustris_trash_manifest() -> Option<PathBuf> {
+    rustris_data_dir().map(|d| d.join("trash.yml"))
+}
+
+/// Get the cached release notes of runner releases
+/// Returns: ~/.local/share/rustris/release_notes.yml
+pub fn rustris_release_notes_cache() -> Option<PathBuf> {
+    rustris_data_dir().map(|d| d.join("release_notes.yml"))
+}
+
 // ============================================================================
 // Cover Art Lookups
 // ============================================================================
@@ -167,22 +234,43 @@ pub fn find_cover_art(slug: &str) -> Option<PathBuf> {
 // Steam/Compatibility Tools Directories
 // ============================================================================
 
+/// Get all Steam installation roots (native and Flatpak)
+/// Returns the existing roots; ~/.steam/root is usually a symlink to one of the others
+pub fn steam_root_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(home) = home_dir() {
+        // Steam installed via system package
+        dirs.push(home.join(".steam/root"));
+        dirs.push(home.join(".local/share/Steam"));
+
+        // Steam Flatpak
+        dirs.push(home.join(".var/app/com.valvesoftware.Steam/data/Steam"));
+        dirs.push(home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+    }
+
+    dirs.into_iter().filter(|d| d.exists()).collect()
+}
+
 /// Get all Steam compatibility tools directories
-/// Returns paths to check for Steam-installed Proton versions
-pub fn steam_compat_tools_dirs() -> Vec<PathBuf> {
+/// Returns: Vec<(PathBuf, &str)> where the string is the source name, "Steam" or "Steam Flatpak"
+pub fn steam_compat_tools_dirs() -> Vec<(PathBuf, &'static str)> {
     let mut dirs = Vec::new();
 
     if let Some(home) = home_dir() {
         // Steam installed via system package
-        dirs.push(home.join(".steam/root/compatibilitytools.d"));
-        dirs.push(home.join(".local/share/Steam/compatibilitytools.d"));
+        dirs.push((home.join(".steam/root/compatibilitytools.d"), "Steam"));
+        dirs.push((home.join(".local/share/Steam/compatibilitytools.d"), "Steam"));
 
         // Steam Flatpak
-        dirs.push(home.join(".var/app/com.valvesoftware.Steam/data/Steam/compatibilitytools.d"));
+        dirs.push((
+            home.join(".var/app/com.valvesoftware.Steam/data/Steam/compatibilitytools.d"),
+            "Steam Flatpak",
+        ));
     }
 
     // Filter to only existing directories
-    dirs.into_iter().filter(|d| d.exists()).collect()
+    dirs.into_iter().filter(|(d, _)| d.exists()).collect()
 }
 
 // ============================================================================
@@ -229,13 +317,7 @@ pub fn wine_scan_locations() -> Vec<(PathBuf, &'static str)> {
     }
 
     // Steam compatibility tools
-    for steam_dir in steam_compat_tools_dirs() {
-        if steam_dir.to_string_lossy().contains("flatpak") {
-            locations.push((steam_dir, "Steam Flatpak"));
-        } else {
-            locations.push((steam_dir, "Steam"));
-        }
-    }
+    locations.extend(steam_compat_tools_dirs());
 
     // Filter to only existing directories
     locations.into_iter().filter(|(d, _)| d.exists()).collect()
@@ -0,0 +1,191 @@
+/// Rustris settings - persisted as YAML in the Rustris data directory
+use crate::rustris_paths;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct RustrisSettings {
+    pub downloads: DownloadSettings,
+    pub github: GitHubSettings,
+    pub network: NetworkSettings,
+    pub retention: RetentionSettings,
+    pub trash: TrashSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadSettings {
+    /// How many runner downloads may run at the same time
+    pub max_concurrent: usize,
+}
+
+impl Default for DownloadSettings {
+    fn default() -> Self {
+        Self { max_concurrent: 2 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct GitHubSettings {
+    /// Personal access token, raises the API rate limit from 60 to 5000 requests per hour
+    pub token: Option<String>,
+    /// Also send the token to a custom `network.github_api_url` (GitHub Enterprise),
+    /// otherwise it only ever goes to api.github.com
+    pub send_token_to_custom_api: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    /// Base URL of the GitHub REST API, RUSTRIS_GITHUB_API_URL overrides it
+    pub github_api_url: String,
+    /// Base URL of the Lutris API, RUSTRIS_LUTRIS_API_URL overrides it
+    pub lutris_api_url: String,
+    /// Proxy for every request, replaces HTTP_PROXY/HTTPS_PROXY when set
+    pub proxy: Option<String>,
+    /// Seconds to wait for a connection
+    pub connect_timeout_secs: u64,
+    /// Seconds an API request may take, and seconds a download may go without receiving data
+    pub timeout_secs: u64,
+    /// How often a failed request is retried
+    pub max_retries: u32,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            github_api_url: "https://api.github.com".to_string(),
+            lutris_api_url: "https://lutris.net/api".to_string(),
+            proxy: None,
+            connect_timeout_secs: 15,
+            timeout_secs: 30,
+            max_retries: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionSettings {
+    /// Remove old Rustris-installed runners after each successful download
+    pub enabled: bool,
+    /// How many of the newest runners to keep per source
+    pub keep_latest: usize,
+    /// Runner paths that are never removed
+    pub pinned: Vec<String>,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keep_latest: 3,
+            pinned: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrashSettings {
+    /// Purge trashed runners older than this many days, 0 keeps them forever
+    pub max_age_days: u64,
+    /// Purge the oldest trashed runners once the trash is larger than this, 0 for no limit
+    pub max_size_mb: u64,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self {
+            max_age_days: 14,
+            max_size_mb: 10240,
+        }
+    }
+}
+
+/// Serializes read-modify-write cycles of the settings file
+static SETTINGS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Load settings from disk, defaults for anything missing
+/// An unreadable file is an error so it is never saved over and the token, pins and endpoints lost
+pub fn load_settings() -> Result<RustrisSettings, String> {
+    let settings_file = match rustris_paths::rustris_settings_file() {
+        Some(f) if f.exists() => f,
+        _ => return Ok(RustrisSettings::default()),
+    };
+
+    let content = fs::read_to_string(&settings_file)
+        .map_err(|e| format!("Failed to read settings {:?}: {}", settings_file, e))?;
+
+    serde_yaml::from_str(&content).map_err(|e| {
+        format!(
+            "Settings {:?} are corrupt, fix or remove them: {}",
+            settings_file, e
+        )
+    })
+}
+
+/// Settings to read from, defaults when the file is unreadable
+/// Never save what this returns, use `update_settings`
+pub fn current_settings() -> RustrisSettings {
+    load_settings().unwrap_or_else(|e| {
+        println!("Warning: {}, using default settings", e);
+        RustrisSettings::default()
+    })
+}
+
+/// Write settings to disk
+fn save_settings(settings: &RustrisSettings) -> Result<(), String> {
+    let settings_file = rustris_paths::rustris_settings_file()
+        .ok_or("Could not get Rustris settings path")?;
+
+    if let Some(parent) = settings_file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create Rustris data directory: {}", e))?;
+    }
+
+    let yaml = serde_yaml::to_string(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    // Write to a temp file first so a crash never leaves truncated settings
+    let tmp_file = settings_file.with_extension("yml.tmp");
+    fs::write(&tmp_file, yaml)
+        .map_err(|e| format!("Failed to write settings: {}", e))?;
+    fs::rename(&tmp_file, &settings_file)
+        .map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+/// Change the settings on disk, returns the saved settings
+/// Fails without writing anything when the current file can't be read
+pub fn update_settings(
+    update: impl FnOnce(&mut RustrisSettings),
+) -> Result<RustrisSettings, String> {
+    let _guard = SETTINGS_LOCK.lock().unwrap();
+    let mut settings = load_settings()?;
+
+    update(&mut settings);
+    save_settings(&settings)?;
+
+    Ok(settings)
+}
+
+/// Get the current Rustris settings
+#[tauri::command]
+pub fn get_rustris_settings() -> Result<RustrisSettings, String> {
+    load_settings()
+}
+
+/// Replace the Rustris settings
+#[tauri::command]
+pub fn save_rustris_settings(settings: RustrisSettings) -> Result<(), String> {
+    let settings = update_settings(|current| *current = settings)?;
+
+    // Apply settings that are held in memory
+    crate::download_manager::get_download_manager()
+        .set_max_concurrent(settings.downloads.max_concurrent);
+
+    Ok(())
+}
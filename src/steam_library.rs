@@ -0,0 +1,148 @@
+/// Steam library discovery - reads libraryfolders.vdf to find every Steam library
+/// and the Valve Proton builds installed in them
+use crate::rustris_paths;
+use crate::vdf;
+use std::path::{Path, PathBuf};
+
+/// Get the library paths listed in a libraryfolders.vdf document
+/// Handles the current format ("0" { "path" "..." }) and the old one ("1" "/path")
+pub fn library_paths_from_vdf(text: &str) -> Result<Vec<PathBuf>, String> {
+    let vdf = vdf::parse(text)?;
+    let folders = vdf
+        .get("libraryfolders")
+        .ok_or("libraryfolders.vdf has no libraryfolders block")?;
+
+    let paths = folders
+        .entries()
+        .iter()
+        // Library entries are numbered, other keys such as "contentstatsid" are not libraries
+        .filter(|(key, _)| key.chars().all(|c| c.is_ascii_digit()))
+        .filter_map(|(_, value)| {
+            value
+                .as_str()
+                .or_else(|| value.get("path").and_then(|p| p.as_str()))
+                .map(PathBuf::from)
+        })
+        .collect();
+
+    Ok(paths)
+}
+
+/// Get every Steam library folder, for native and Flatpak Steam
+/// Each Steam root is a library itself, libraryfolders.vdf lists the others
+pub fn steam_library_dirs() -> Vec<PathBuf> {
+    let mut libraries: Vec<PathBuf> = Vec::new();
+
+    for root in rustris_paths::steam_root_dirs() {
+        let mut candidates = vec![root.clone()];
+
+        for vdf_path in [
+            root.join("steamapps/libraryfolders.vdf"),
+            root.join("config/libraryfolders.vdf"),
+        ] {
+            if !vdf_path.exists() {
+                continue;
+            }
+
+            let parsed = std::fs::read_to_string(&vdf_path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| library_paths_from_vdf(&text));
+
+            match parsed {
+                Ok(paths) => candidates.extend(paths),
+                Err(e) => println!("Warning: could not read {:?}: {}", vdf_path, e),
+            }
+        }
+
+        for candidate in candidates {
+            // The same library is often reachable through ~/.steam/root and its target
+            let library = candidate.canonicalize().unwrap_or(candidate);
+            if library.join("steamapps").is_dir() && !libraries.contains(&library) {
+                libraries.push(library);
+            }
+        }
+    }
+
+    libraries
+}
+
+/// Check whether a steamapps/common folder holds a Valve Proton build
+/// Matches "Proton 9.0", "Proton - Experimental", "Proton Hotfix" and friends
+fn is_valve_proton_dir(dir: &Path) -> bool {
+    let is_proton_name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().starts_with("Proton"))
+        .unwrap_or(false);
+
+    is_proton_name && dir.join("proton").is_file()
+}
+
+/// Get every Valve Proton build installed in any Steam library
+pub fn valve_proton_dirs() -> Vec<PathBuf> {
+    let mut proton_dirs = Vec::new();
+
+    for library in steam_library_dirs() {
+        let common = library.join("steamapps/common");
+        if let Ok(entries) = std::fs::read_dir(&common) {
+            for entry in entries.flatten() {
+                if is_valve_proton_dir(&entry.path()) {
+                    proton_dirs.push(entry.path());
+                }
+            }
+        }
+    }
+
+    proton_dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library_folders() {
+        let text = r#"
+            "libraryfolders"
+            {
+                "0"
+                {
+                    "path"      "/home/user/.local/share/Steam"
+                    "label"     ""
+                    "apps"
+                    {
+                        "1628350"   "123456"
+                    }
+                }
+                "1"
+                {
+                    "path"      "/mnt/games/SteamLibrary"
+                }
+            }
+        "#;
+
+        assert_eq!(
+            library_paths_from_vdf(text).unwrap(),
+            vec![
+                PathBuf::from("/home/user/.local/share/Steam"),
+                PathBuf::from("/mnt/games/SteamLibrary"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_legacy_library_folders() {
+        let text = r#"
+            "LibraryFolders"
+            {
+                "TimeNextStatsReport"   "1700000000"
+                "ContentStatsID"        "-123"
+                "1"                     "/mnt/games/SteamLibrary"
+            }
+        "#;
+
+        assert_eq!(
+            library_paths_from_vdf(text).unwrap(),
+            vec![PathBuf::from("/mnt/games/SteamLibrary")]
+        );
+    }
+}
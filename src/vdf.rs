@@ -0,0 +1,217 @@
+/// Minimal parser for Valve's text KeyValues (VDF) format
+/// Used for compatibilitytool.vdf, toolmanifest.vdf and libraryfolders.vdf
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A VDF value: either a string or a nested block of key/value pairs
+#[derive(Debug, Clone, PartialEq)]
+pub enum VdfValue {
+    String(String),
+    Object(Vec<(String, VdfValue)>),
+}
+
+impl VdfValue {
+    /// Look up a key in a block, case-insensitively like Steam does
+    pub fn get(&self, key: &str) -> Option<&VdfValue> {
+        self.entries()
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+
+    /// Follow a chain of keys through nested blocks
+    pub fn get_path(&self, keys: &[&str]) -> Option<&VdfValue> {
+        keys.iter().try_fold(self, |value, key| value.get(key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            VdfValue::String(s) => Some(s),
+            VdfValue::Object(_) => None,
+        }
+    }
+
+    /// Key/value pairs of a block, empty for a string
+    pub fn entries(&self) -> &[(String, VdfValue)] {
+        match self {
+            VdfValue::Object(entries) => entries,
+            VdfValue::String(_) => &[],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Open,
+    Close,
+}
+
+struct Tokenizer<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn next_token(&mut self) -> Result<Option<Token>, String> {
+        loop {
+            match self.chars.peek() {
+                None => return Ok(None),
+                Some(c) if c.is_whitespace() => {
+                    self.chars.next();
+                }
+                Some('/') => {
+                    // Line comment
+                    self.chars.next();
+                    if self.chars.peek() != Some(&'/') {
+                        return Err("Unexpected '/' in VDF".to_string());
+                    }
+                    for c in self.chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                Some('[') => {
+                    // Platform conditional such as [$WIN32], ignored
+                    for c in self.chars.by_ref() {
+                        if c == ']' {
+                            break;
+                        }
+                    }
+                }
+                Some('{') => {
+                    self.chars.next();
+                    return Ok(Some(Token::Open));
+                }
+                Some('}') => {
+                    self.chars.next();
+                    return Ok(Some(Token::Close));
+                }
+                Some('"') => {
+                    self.chars.next();
+                    return self.quoted().map(|s| Some(Token::Text(s)));
+                }
+                Some(_) => return Ok(Some(Token::Text(self.unquoted()))),
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        let mut text = String::new();
+
+        while let Some(c) = self.chars.next() {
+            match c {
+                '"' => return Ok(text),
+                '\\' => match self.chars.next() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(other) => text.push(other),
+                    None => break,
+                },
+                _ => text.push(c),
+            }
+        }
+
+        Err("Unterminated string in VDF".to_string())
+    }
+
+    fn unquoted(&mut self) -> String {
+        let mut text = String::new();
+
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                break;
+            }
+            text.push(c);
+            self.chars.next();
+        }
+
+        text
+    }
+}
+
+/// Parse the pairs of one block, up to its closing brace (or the end of input for the root)
+fn parse_block(tokens: &mut Tokenizer, is_root: bool) -> Result<VdfValue, String> {
+    let mut entries = Vec::new();
+
+    loop {
+        let key = match tokens.next_token()? {
+            Some(Token::Text(key)) => key,
+            Some(Token::Close) if !is_root => return Ok(VdfValue::Object(entries)),
+            None if is_root => return Ok(VdfValue::Object(entries)),
+            None => return Err("Unexpected end of VDF, missing '}'".to_string()),
+            Some(token) => return Err(format!("Expected a key in VDF, found {:?}", token)),
+        };
+
+        let value = match tokens.next_token()? {
+            Some(Token::Text(value)) => VdfValue::String(value),
+            Some(Token::Open) => parse_block(tokens, false)?,
+            _ => return Err(format!("Missing value for key \"{}\" in VDF", key)),
+        };
+
+        entries.push((key, value));
+    }
+}
+
+/// Parse a VDF document into its top-level block
+pub fn parse(text: &str) -> Result<VdfValue, String> {
+    let mut tokens = Tokenizer {
+        chars: text.chars().peekable(),
+    };
+    parse_block(&mut tokens, true)
+}
+
+/// Read and parse a VDF file
+pub fn parse_file(path: &std::path::Path) -> Result<VdfValue, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    parse(&text).map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compatibilitytool() {
+        let text = r#"
+            "compatibilitytools"
+            {
+              "compat_tools"
+              {
+                "GE-Proton10-27" // Internal name of this tool
+                {
+                  "install_path" "."
+                  "display_name" "GE-Proton10-27"
+                  "from_oslist"  "windows"
+                  "to_oslist"    "linux"
+                }
+              }
+            }
+        "#;
+
+        let vdf = parse(text).unwrap();
+        let tools = vdf.get_path(&["compatibilitytools", "compat_tools"]).unwrap();
+        let (name, tool) = &tools.entries()[0];
+
+        assert_eq!(name, "GE-Proton10-27");
+        assert_eq!(tool.get("display_name").and_then(|v| v.as_str()), Some("GE-Proton10-27"));
+        assert_eq!(tool.get("TO_OSLIST").and_then(|v| v.as_str()), Some("linux"));
+    }
+
+    #[test]
+    fn test_escapes_and_unquoted() {
+        let vdf = parse(r#"manifest { commandline "/proton %verb%" path "C:\\Games\\\"x\"" version 2 }"#).unwrap();
+
+        assert_eq!(vdf.get_path(&["manifest", "commandline"]).and_then(|v| v.as_str()), Some("/proton %verb%"));
+        assert_eq!(vdf.get_path(&["manifest", "path"]).and_then(|v| v.as_str()), Some("C:\\Games\\\"x\""));
+        assert_eq!(vdf.get_path(&["manifest", "version"]).and_then(|v| v.as_str()), Some("2"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse(r#""a" { "b" "c""#).is_err());
+        assert!(parse(r#""a" "unterminated"#).is_err());
+        assert!(parse(r#""a""#).is_err());
+    }
+}